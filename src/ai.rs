@@ -0,0 +1,271 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    time::Duration,
+};
+
+use bevy::{
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, Task},
+};
+use bevy_chess::{
+    engine::{
+        search_with_limits, Book, BookSelection, EvalWeights, PolyglotKeys, SearchLimits,
+        SearchResult, TranspositionTable,
+    },
+    rules::{Move, Team},
+};
+use futures_lite::future;
+use iyes_loopless::prelude::*;
+
+use crate::{
+    external::ExternalEngine,
+    game::{game_in_progress, BoardResetEvent, GameState},
+    movement::PlayMoveEvent,
+    promotion::PendingPromotion,
+    replay::Replay,
+};
+
+// the computer plays this team, searching within `limits` or, when it has a clock, for as
+// long as the clock allows
+#[derive(Resource)]
+pub struct AiPlayer {
+    pub team: Team,
+    pub limits: SearchLimits,
+    pub clock: Option<AiClock>,
+    // shared with the searches running in the background
+    pub weights: Arc<EvalWeights>,
+    pub table: Arc<Mutex<TranspositionTable>>,
+    pub book: Option<AiBook>,
+}
+
+// an opening book the computer plays from instead of searching, for the first `plies` plies
+// of the game and as long as the book has a move
+pub struct AiBook {
+    pub book: Book,
+    pub keys: PolyglotKeys,
+    pub selection: BookSelection,
+    pub plies: usize,
+}
+
+impl AiBook {
+    fn choose(&self, game: &GameState) -> Option<Move> {
+        if game.game.moves().len() >= self.plies {
+            return None;
+        }
+
+        self.book
+            .choose(&self.keys, game.position(), self.selection)
+    }
+}
+
+// the computer's time control, `time` for the whole game and `increment` more after every
+// move. The players don't have one, only the computer has to budget its time
+pub struct AiClock {
+    pub time: Duration,
+    pub increment: Duration,
+}
+
+impl AiClock {
+    // what's left on the clock with the moves `team` made so far
+    fn remaining(&self, game: &GameState, team: Team) -> Duration {
+        // the first move was made by the side to move in the starting position
+        let first = usize::from(game.game.start().side_to_move() != team);
+        let (moves, used) = game
+            .move_times
+            .iter()
+            .skip(first)
+            .step_by(2)
+            .fold((0, Duration::ZERO), |(moves, used), time| {
+                (moves + 1, used + *time)
+            });
+
+        (self.time + self.increment * moves).saturating_sub(used)
+    }
+}
+
+// a search running in the background, for the position with the given key
+#[derive(Resource)]
+pub struct AiSearch {
+    task: Task<SearchResult>,
+    zobrist: u64,
+    stop: Arc<AtomicBool>,
+}
+
+impl Drop for AiSearch {
+    // dropping the task only cancels it between polls, the flag makes the search itself
+    // return early
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+// run condition for the systems that let the players move pieces, the pieces of the computer
+// and of an external engine are off limits to them
+pub fn human_to_move(
+    game: Res<GameState>,
+    ai: Option<Res<AiPlayer>>,
+    engine: Option<Res<ExternalEngine>>,
+) -> bool {
+    let side = game.side_to_move();
+    !matches!(ai, Some(ai) if ai.team == side)
+        && !matches!(engine, Some(engine) if engine.team == side)
+}
+
+pub struct AiPlugin;
+
+impl Plugin for AiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            Self::start_search
+                .run_if_resource_exists::<AiPlayer>()
+                .run_if(game_in_progress)
+                .run_unless_resource_exists::<AiSearch>()
+                .run_unless_resource_exists::<Replay>()
+                .run_unless_resource_exists::<PendingPromotion>(),
+        )
+        .add_system(Self::poll_search.run_if_resource_exists::<AiSearch>());
+    }
+}
+
+impl AiPlugin {
+    // starts searching on the background task pool when it's the computer's turn, so the
+    // frames keep coming while it thinks. A move from the book is played right away
+    fn start_search(
+        mut commands: Commands,
+        ai: Res<AiPlayer>,
+        game: Res<GameState>,
+        mut play_move: EventWriter<PlayMoveEvent>,
+        // the position the last search was started for, so it isn't started again before
+        // its move has been played
+        mut searched: Local<Option<(usize, u64)>>,
+        mut board_reset: EventReader<BoardResetEvent>,
+    ) {
+        // a new game can start from the position searched last
+        if board_reset.iter().count() > 0 {
+            *searched = None;
+        }
+        if game.side_to_move() != ai.team {
+            *searched = None;
+            return;
+        }
+
+        let current = (game.game.moves().len(), game.zobrist());
+        if *searched == Some(current) {
+            return;
+        }
+        *searched = Some(current);
+
+        if let Some(mv) = ai.book.as_ref().and_then(|book| book.choose(&game)) {
+            info!("computer plays {} from the book", game.position().san(mv));
+            play_move.send(PlayMoveEvent(mv));
+            return;
+        }
+
+        let position = game.position().clone();
+        let limits = match &ai.clock {
+            Some(clock) => {
+                SearchLimits::from_clock(clock.remaining(&game, ai.team), clock.increment, None)
+            }
+            None => ai.limits,
+        };
+        let weights = ai.weights.clone();
+        let table = ai.table.clone();
+        let stop = Arc::new(AtomicBool::new(false));
+        let task_stop = stop.clone();
+        let task = AsyncComputeTaskPool::get().spawn(async move {
+            // a cancelled search still holding the table gives it back as soon as it sees
+            // its stop flag
+            let mut table = table.lock().unwrap_or_else(PoisonError::into_inner);
+            search_with_limits(&position, limits, &weights, &mut table, &task_stop)
+        });
+
+        commands.insert_resource(AiSearch {
+            task,
+            zobrist: game.zobrist(),
+            stop,
+        });
+    }
+
+    // plays the move once the search is done. A new game removes the search before it's
+    // done, and so does the game ending or the position changing under it some other way
+    fn poll_search(
+        mut commands: Commands,
+        mut search: ResMut<AiSearch>,
+        game: Res<GameState>,
+        mut play_move: EventWriter<PlayMoveEvent>,
+    ) {
+        if search.zobrist != game.zobrist() || game.outcome.is_some() {
+            info!("computer search cancelled");
+            commands.remove_resource::<AiSearch>();
+            return;
+        }
+
+        let result = match future::block_on(future::poll_once(&mut search.task)) {
+            Some(result) => result,
+            None => return,
+        };
+        commands.remove_resource::<AiSearch>();
+
+        if let Some(mv) = result.best_move {
+            info!(
+                "computer plays {} (score {}, depth {}, {} nodes)",
+                game.position().san(mv),
+                result.score,
+                result.depth,
+                result.stats.nodes
+            );
+            play_move.send(PlayMoveEvent(mv));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::tasks::TaskPool;
+
+    use super::*;
+    use crate::{game::GamePlugin, movement::MoveEvent};
+
+    #[test]
+    fn a_new_game_stops_the_search() {
+        AsyncComputeTaskPool::init(TaskPool::default);
+        let mut app = App::new();
+        app.init_resource::<Input<KeyCode>>()
+            .init_resource::<Time>()
+            .init_resource::<Windows>()
+            .add_event::<MoveEvent>()
+            .add_event::<PlayMoveEvent>()
+            .insert_resource(GameState::default())
+            // deep enough to still be searching when the game starts over
+            .insert_resource(AiPlayer {
+                team: Team::White,
+                limits: SearchLimits {
+                    depth: Some(64),
+                    ..SearchLimits::default()
+                },
+                clock: None,
+                weights: Arc::new(EvalWeights::default()),
+                table: Arc::new(Mutex::new(TranspositionTable::new(1))),
+                book: None,
+            })
+            .add_plugin(GamePlugin)
+            .add_plugin(AiPlugin);
+
+        app.update();
+        let stop = app.world.resource::<AiSearch>().stop.clone();
+        assert!(!stop.load(Ordering::Relaxed));
+
+        app.world.resource_mut::<Input<KeyCode>>().press(KeyCode::N);
+        app.update();
+        app.world.resource_mut::<Input<KeyCode>>().clear();
+        assert!(stop.load(Ordering::Relaxed));
+        assert!(!app.world.contains_resource::<AiSearch>());
+
+        // the new game starts from the position searched last, it's searched again
+        app.update();
+        assert!(!Arc::ptr_eq(&app.world.resource::<AiSearch>().stop, &stop));
+    }
+}
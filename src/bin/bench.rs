@@ -0,0 +1,85 @@
+//! Searches a fixed set of positions to a fixed depth and prints what it took, to measure
+//! changes to the search: the same depth in fewer nodes is a better search.
+//!
+//! `cargo run --release --bin bench -- [depth] [hash megabytes]`, depth 5 and a 16 MB
+//! table by default. A table of 0 MB turns it off.
+use std::{env, process, sync::atomic::AtomicBool, time::Instant};
+
+use bevy_chess::{
+    engine::{search_with_limits, EvalWeights, SearchLimits, SearchStats, TranspositionTable},
+    rules::Position,
+};
+
+const POSITIONS: [&str; 8] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+    "r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4",
+    "8/8/4k3/3p4/3P4/4K3/8/8 w - - 0 1",
+];
+
+fn parsed_arg(arg: Option<String>, default: usize) -> usize {
+    match arg.map(|arg| arg.parse()) {
+        Some(Ok(value)) => value,
+        Some(Err(_)) => {
+            eprintln!("usage: bench [depth] [hash megabytes]");
+            process::exit(2);
+        }
+        None => default,
+    }
+}
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let depth = parsed_arg(args.next(), 5) as u32;
+    let megabytes = parsed_arg(args.next(), 16);
+
+    let weights = EvalWeights::default();
+    let mut table = TranspositionTable::new(megabytes);
+    let mut total = SearchStats::default();
+    let started = Instant::now();
+
+    for fen in POSITIONS {
+        let position = Position::from_fen(fen).expect("the bench positions are valid");
+        // every position starts from scratch, so the numbers don't depend on the order
+        table.clear();
+        let result = search_with_limits(
+            &position,
+            SearchLimits::depth(depth),
+            &weights,
+            &mut table,
+            &AtomicBool::new(false),
+        );
+
+        let best_move = result
+            .best_move
+            .map_or("-".to_string(), |mv| position.san(mv));
+        println!(
+            "{:<8} {:>7} {:>10} nodes  {}",
+            best_move, result.score, result.stats.nodes, fen
+        );
+
+        total.nodes += result.stats.nodes;
+        total.table_hits += result.stats.table_hits;
+        total.cutoffs += result.stats.cutoffs;
+        total.first_move_cutoffs += result.stats.first_move_cutoffs;
+    }
+
+    let elapsed = started.elapsed();
+    println!();
+    println!("Nodes searched: {}", total.nodes);
+    println!("Table hits: {}", total.table_hits);
+    println!(
+        "Cutoffs: {} ({:.1}% by the first move)",
+        total.cutoffs,
+        100.0 * total.first_move_cutoffs as f64 / total.cutoffs.max(1) as f64
+    );
+    println!(
+        "Time: {:.3}s ({:.0} nodes/s)",
+        elapsed.as_secs_f64(),
+        total.nodes as f64 / elapsed.as_secs_f64().max(f64::EPSILON)
+    );
+}
@@ -0,0 +1,65 @@
+//! Builds a Polyglot opening book from a collection of games, for the computer player's
+//! `--ai-book`.
+//!
+//! `cargo run --release --bin book -- <games.pgn> <book.bin> [plies] [keys]`, the first 16
+//! plies of every game by default. The keys are Polyglot's own unless a file of random
+//! numbers is given.
+use std::{env, fs, process};
+
+use bevy_chess::{
+    engine::{Book, PolyglotKeys},
+    rules::Pgn,
+};
+
+const USAGE: &str = "usage: book <games.pgn> <book.bin> [plies] [keys]";
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let (games, book) = match (args.next(), args.next()) {
+        (Some(games), Some(book)) => (games, book),
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+    let plies = match args.next().map(|plies| plies.parse()) {
+        Some(Ok(plies)) => plies,
+        Some(Err(_)) => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+        None => 16,
+    };
+    let keys = match args.next() {
+        Some(keys) => PolyglotKeys::load(&keys).unwrap_or_else(|err| {
+            eprintln!("invalid keys in {}: {}", keys, err);
+            process::exit(2);
+        }),
+        None => PolyglotKeys::default(),
+    };
+    let pgn = fs::read_to_string(&games).unwrap_or_else(|err| {
+        eprintln!("couldn't read {}: {}", games, err);
+        process::exit(2);
+    });
+
+    // a game that can't be read is skipped, the rest still make a book
+    let mut read = Vec::new();
+    for (i, game) in Pgn::parse_all(&pgn).into_iter().enumerate() {
+        match game {
+            Ok(game) => read.push(game),
+            Err(err) => eprintln!("skipping game {}: {}", i + 1, err),
+        }
+    }
+
+    let built = Book::build(&keys, &read, plies);
+    if let Err(err) = built.save(&book) {
+        eprintln!("couldn't write {}: {}", book, err);
+        process::exit(1);
+    }
+    println!(
+        "{} moves from {} games written to {}",
+        built.len(),
+        read.len(),
+        book
+    );
+}
@@ -0,0 +1,47 @@
+//! Counts the leaf nodes of the move tree from a position, split by the first move.
+//!
+//! `cargo run --release --bin perft -- <depth> [fen]`, the start position is used when no
+//! FEN is given.
+use std::{env, process, time::Instant};
+
+use bevy_chess::rules::{divide, Position};
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let depth = match args.next().map(|depth| depth.parse::<u32>()) {
+        Some(Ok(depth)) => depth,
+        _ => {
+            eprintln!("usage: perft <depth> [fen]");
+            process::exit(2);
+        }
+    };
+
+    // the FEN fields come as separate arguments unless they were quoted
+    let fen = args.collect::<Vec<_>>().join(" ");
+    let position = if fen.is_empty() {
+        Position::default()
+    } else {
+        Position::from_fen(&fen).unwrap_or_else(|err| {
+            eprintln!("invalid fen {:?}: {}", fen, err);
+            process::exit(2);
+        })
+    };
+
+    let started = Instant::now();
+    let mut moves = divide(&position, depth);
+    moves.sort_by_key(|(mv, _)| mv.to_string());
+
+    for (mv, nodes) in &moves {
+        println!("{}: {}", mv, nodes);
+    }
+
+    let nodes: u64 = moves.iter().map(|(_, nodes)| nodes).sum();
+    let elapsed = started.elapsed();
+    println!();
+    println!("Nodes searched: {}", nodes);
+    println!(
+        "Time: {:.3}s ({:.0} nodes/s)",
+        elapsed.as_secs_f64(),
+        nodes as f64 / elapsed.as_secs_f64().max(f64::EPSILON)
+    );
+}
@@ -0,0 +1,167 @@
+//! The engine behind the game as a UCI engine, to play it from chess GUIs and tournament
+//! managers: `cargo build --release --bin uci` and point the GUI at `target/release/uci`.
+//!
+//! With `--xboard` it speaks the xboard protocol instead, for xboard, WinBoard and the
+//! tools built for them.
+mod search;
+mod xboard;
+
+use std::{
+    io::{self, BufRead},
+    sync::{atomic::AtomicBool, Arc, Mutex, PoisonError},
+    time::Duration,
+};
+
+use bevy_chess::{
+    engine::{EvalWeights, SearchResult, TranspositionTable},
+    rules::Position,
+    uci::{self, Command, Go},
+};
+use search::{principal_variation, wait_for_stop, Search};
+
+const DEFAULT_HASH: usize = 16;
+const MAX_HASH: usize = 1024;
+struct Engine {
+    position: Position,
+    weights: Arc<EvalWeights>,
+    table: Arc<Mutex<TranspositionTable>>,
+    search: Option<Search<()>>,
+}
+
+impl Engine {
+    fn new() -> Self {
+        Self {
+            position: Position::default(),
+            weights: Arc::new(EvalWeights::default()),
+            table: Arc::new(Mutex::new(TranspositionTable::new(DEFAULT_HASH))),
+            search: None,
+        }
+    }
+
+    fn identify(&self) {
+        println!(
+            "id name {} {}",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION")
+        );
+        println!("id author {}", env!("CARGO_PKG_AUTHORS"));
+        println!(
+            "option name Hash type spin default {} min 0 max {}",
+            DEFAULT_HASH, MAX_HASH
+        );
+        println!("option name EvalFile type string default <empty>");
+        println!("uciok");
+    }
+
+    fn set_option(&mut self, name: &str, value: Option<&str>) {
+        // the running search holds on to the table and the weights
+        self.stop();
+
+        match (name.to_ascii_lowercase().as_str(), value) {
+            ("hash", Some(value)) => match value.parse::<usize>() {
+                Ok(megabytes) => {
+                    self.table =
+                        Arc::new(Mutex::new(TranspositionTable::new(megabytes.min(MAX_HASH))));
+                }
+                Err(_) => println!("info string invalid Hash value {:?}", value),
+            },
+            ("evalfile", Some("<empty>") | None) => {
+                self.weights = Arc::new(EvalWeights::default());
+            }
+            ("evalfile", Some(path)) => match EvalWeights::load(path) {
+                Ok(weights) => self.weights = Arc::new(weights),
+                Err(err) => println!("info string {}: {}", path, err),
+            },
+            _ => println!("info string unknown option {:?}", name),
+        }
+    }
+
+    fn new_game(&mut self) {
+        self.stop();
+        self.table
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+
+    fn go(&mut self, go: Go) {
+        self.stop();
+
+        let position = self.position.clone();
+        let limits = go.limits(position.side_to_move());
+        let report = move |result: &SearchResult, table: &TranspositionTable, elapsed: Duration| {
+            println!(
+                "info depth {} score {} nodes {} nps {} time {} hashfull {} pv {}",
+                result.depth,
+                uci::score(result.score),
+                result.stats.nodes,
+                (result.stats.nodes as f64 / elapsed.as_secs_f64().max(0.001)) as u64,
+                elapsed.as_millis(),
+                table.permill_full(),
+                principal_variation(table, &position)
+            );
+        };
+        let finish = move |result: SearchResult, stop: &AtomicBool| {
+            // an infinite search only ends with `stop`, even when there's nothing left to
+            // find out
+            if go.infinite {
+                wait_for_stop(stop);
+            }
+
+            match result.best_move {
+                Some(mv) => println!("bestmove {}", mv),
+                // mated or stalemated, there's no move to give
+                None => println!("bestmove 0000"),
+            }
+        };
+
+        self.search = Some(Search::spawn(
+            self.position.clone(),
+            limits,
+            self.weights.clone(),
+            self.table.clone(),
+            report,
+            finish,
+        ));
+    }
+
+    // ends the running search, which sends its best move before it's done
+    fn stop(&mut self) {
+        if let Some(search) = self.search.take() {
+            search.stop();
+        }
+    }
+}
+
+fn main() {
+    if std::env::args().any(|arg| arg == "--xboard") {
+        xboard::run();
+        return;
+    }
+
+    let mut engine = Engine::new();
+
+    for line in io::stdin().lock().lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match line.parse() {
+            Ok(Command::Uci) => engine.identify(),
+            Ok(Command::IsReady) => println!("readyok"),
+            Ok(Command::SetOption { name, value }) => engine.set_option(&name, value.as_deref()),
+            Ok(Command::UciNewGame) => engine.new_game(),
+            Ok(Command::Position(position)) => engine.position = *position,
+            Ok(Command::Go(go)) => engine.go(go),
+            Ok(Command::Stop) => engine.stop(),
+            Ok(Command::Quit) => break,
+            Err(err) => println!("info string {}", err),
+        }
+    }
+
+    engine.stop();
+}
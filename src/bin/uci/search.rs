@@ -0,0 +1,85 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use bevy_chess::{
+    engine::{search_with_report, EvalWeights, SearchLimits, SearchResult, TranspositionTable},
+    rules::Position,
+};
+
+// the longest line of moves shown with each depth
+const MAX_PV: usize = 16;
+
+// a search running on another thread, so commands can still be read while it runs
+pub struct Search<T> {
+    thread: JoinHandle<T>,
+    stop: Arc<AtomicBool>,
+}
+
+impl<T: Send + 'static> Search<T> {
+    // `report` is told about every depth with the time it took so far, and `finish` gets
+    // the result. It can wait for the stop flag before it gives the best move
+    pub fn spawn(
+        position: Position,
+        limits: SearchLimits,
+        weights: Arc<EvalWeights>,
+        table: Arc<Mutex<TranspositionTable>>,
+        mut report: impl FnMut(&SearchResult, &TranspositionTable, Duration) + Send + 'static,
+        finish: impl FnOnce(SearchResult, &AtomicBool) -> T + Send + 'static,
+    ) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let search_stop = stop.clone();
+
+        let thread = thread::spawn(move || {
+            let started = Instant::now();
+            let mut table = table.lock().unwrap_or_else(PoisonError::into_inner);
+            let result = search_with_report(
+                &position,
+                limits,
+                &weights,
+                &mut table,
+                &search_stop,
+                |result, table| report(result, table, started.elapsed()),
+            );
+
+            finish(result, &search_stop)
+        });
+
+        Self { thread, stop }
+    }
+
+    // waits until the search is over on its own, without stopping it
+    pub fn wait(self) -> Option<T> {
+        self.thread.join().ok()
+    }
+
+    // ends the search, `finish` still runs. `None` only when the search panicked, which
+    // only happens on a bug, the engine keeps going without it
+    pub fn stop(self) -> Option<T> {
+        self.stop.store(true, Ordering::Relaxed);
+        self.thread.thread().unpark();
+        self.thread.join().ok()
+    }
+}
+
+// waits until the search is stopped
+pub fn wait_for_stop(stop: &AtomicBool) {
+    while !stop.load(Ordering::Relaxed) {
+        thread::park();
+    }
+}
+
+// the moves the search expects from `position`, space separated
+pub fn principal_variation(table: &TranspositionTable, position: &Position) -> String {
+    table
+        .principal_variation(position, MAX_PV)
+        .iter()
+        .map(|mv| mv.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
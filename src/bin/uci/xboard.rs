@@ -0,0 +1,195 @@
+use std::{
+    io::{self, BufRead},
+    sync::{atomic::AtomicBool, Arc, Mutex, PoisonError},
+    time::Duration,
+};
+
+use bevy_chess::{
+    engine::{EvalWeights, SearchResult, TranspositionTable},
+    rules::{Move, Position, Team},
+    xboard::{self, Command, TimeControl, XboardError},
+};
+
+use crate::search::{principal_variation, Search};
+
+const HASH: usize = 16;
+
+struct Engine {
+    position: Position,
+    // the side the engine plays, none in force mode
+    team: Option<Team>,
+    control: TimeControl,
+    // what's left on the engine's clock, as `time` last said
+    clock: Option<Duration>,
+    post: bool,
+    weights: Arc<EvalWeights>,
+    table: Arc<Mutex<TranspositionTable>>,
+    search: Option<Search<Option<Move>>>,
+}
+
+impl Engine {
+    fn new() -> Self {
+        Self {
+            position: Position::default(),
+            team: None,
+            control: TimeControl::default(),
+            clock: None,
+            post: false,
+            weights: Arc::new(EvalWeights::default()),
+            table: Arc::new(Mutex::new(TranspositionTable::new(HASH))),
+            search: None,
+        }
+    }
+
+    fn features(&self) {
+        println!(
+            "feature myname=\"{} {}\" usermove=1 setboard=1 ping=1 playother=0 colors=0 \
+             sigint=0 sigterm=0 done=1",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION")
+        );
+    }
+
+    fn new_game(&mut self) {
+        self.finish();
+        self.position = Position::default();
+        self.team = Some(Team::Black);
+        // `new` takes back `sd`, the level stays
+        self.control.depth = None;
+        self.clock = None;
+        self.table
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+
+    fn user_move(&mut self, uci: &str) {
+        self.finish();
+
+        match self.position.parse_uci(uci) {
+            Ok(mv) => {
+                self.position.apply(mv);
+                self.think();
+            }
+            Err(_) => println!("Illegal move: {}", uci),
+        }
+    }
+
+    // starts a search when it's the engine's turn and there's a move to play
+    fn think(&mut self) {
+        if self.team != Some(self.position.side_to_move()) || self.position.outcome().is_some() {
+            return;
+        }
+
+        let position = self.position.clone();
+        let limits = self
+            .control
+            .limits(self.clock, self.position.fullmove_number());
+        let post = self.post;
+        // thinking output: depth, score, time in centiseconds, nodes and the line
+        let report = move |result: &SearchResult, table: &TranspositionTable, elapsed: Duration| {
+            if post {
+                println!(
+                    "{} {} {} {} {}",
+                    result.depth,
+                    xboard::score(result.score),
+                    elapsed.as_millis() / 10,
+                    result.stats.nodes,
+                    principal_variation(table, &position)
+                );
+            }
+        };
+        let finish = |result: SearchResult, _: &AtomicBool| {
+            if let Some(mv) = result.best_move {
+                println!("move {}", mv);
+            }
+            result.best_move
+        };
+
+        self.search = Some(Search::spawn(
+            self.position.clone(),
+            limits,
+            self.weights.clone(),
+            self.table.clone(),
+            report,
+            finish,
+        ));
+    }
+
+    // ends the running search, and plays the move it sent
+    fn finish(&mut self) {
+        if let Some(Some(mv)) = self.search.take().and_then(Search::stop) {
+            self.position.apply(mv);
+        }
+    }
+
+    // lets the running search find its move and plays it, so everything sent before a
+    // `ping` is done when `pong` goes out
+    fn settle(&mut self) {
+        if let Some(Some(mv)) = self.search.take().and_then(Search::wait) {
+            self.position.apply(mv);
+        }
+    }
+}
+
+pub fn run() {
+    let mut engine = Engine::new();
+
+    for line in io::stdin().lock().lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match line.parse() {
+            Ok(Command::Xboard | Command::Accepted(_) | Command::Rejected(_)) => {}
+            Ok(Command::Protover(_)) => engine.features(),
+            Ok(Command::New) => engine.new_game(),
+            Ok(Command::Force) => {
+                engine.finish();
+                engine.team = None;
+            }
+            Ok(Command::Go) => {
+                engine.finish();
+                engine.team = Some(engine.position.side_to_move());
+                engine.think();
+            }
+            Ok(Command::UserMove(uci)) => engine.user_move(&uci),
+            Ok(Command::Level(level)) => {
+                engine.control.level = Some(level);
+                engine.control.move_time = None;
+            }
+            Ok(Command::St(move_time)) => {
+                engine.control.move_time = Some(move_time);
+                engine.control.level = None;
+            }
+            Ok(Command::Sd(depth)) => engine.control.depth = Some(depth),
+            Ok(Command::Time(time)) => engine.clock = Some(time),
+            Ok(Command::Otim(_)) => {}
+            Ok(Command::SetBoard(position)) => {
+                engine.finish();
+                engine.position = *position;
+            }
+            Ok(Command::Ping(number)) => {
+                engine.settle();
+                println!("pong {}", number);
+            }
+            Ok(Command::MoveNow) => engine.finish(),
+            Ok(Command::Post) => engine.post = true,
+            Ok(Command::NoPost) => engine.post = false,
+            Ok(Command::Result { .. }) => {
+                engine.finish();
+                engine.team = None;
+            }
+            Ok(Command::Quit) => break,
+            Err(XboardError::Unknown(_)) => println!("Error (unknown command): {}", line),
+            Err(XboardError::Fen(_)) => println!("tellusererror Illegal position"),
+            Err(err) => println!("Error ({}): {}", err, line),
+        }
+    }
+
+    engine.finish();
+}
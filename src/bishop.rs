@@ -7,15 +7,14 @@ use bevy::{
     utils::default,
 };
 use bevy_ecs_tilemap::{
-    helpers::square_grid::neighbors::{Neighbors, SquareDirection},
-    prelude::{TilemapGridSize, TilemapSize, TilemapType},
+    prelude::{TilemapGridSize, TilemapType},
     tiles::{TilePos, TileStorage},
 };
 use bevy_mod_picking::PickableBundle;
 
 use crate::{
     board::{Tile, TileState},
-    piece::{highlight_tile, PieceType, Team},
+    piece::{PieceType, Team},
 };
 
 #[derive(Component, Clone, Copy)]
@@ -23,87 +22,6 @@ pub struct Bishop {
     pub team: Team,
 }
 
-impl Bishop {
-    pub fn movement(
-        self,
-        commands: &mut Commands,
-        tile_storage: &TileStorage,
-        grid_size: &TilemapGridSize,
-        map_size: &TilemapSize,
-        map_type: &TilemapType,
-        tile_state_q: &mut Query<&mut TileState>,
-        piece_type: &Query<&PieceType>,
-        tile_pos: TilePos,
-        mesh: &mut Assets<Mesh>,
-        material: &mut Assets<ColorMaterial>,
-    ) {
-        let dir: Vec<SquareDirection> = vec![
-            SquareDirection::NorthWest,
-            SquareDirection::NorthEast,
-            SquareDirection::SouthWest,
-            SquareDirection::SouthEast,
-        ];
-        let tile_neighbors = Neighbors::get_square_neighboring_positions(&tile_pos, map_size, true);
-
-        // spawn in every specified direction
-        dir.iter().for_each(|dir| {
-            if let Some(pos) = tile_neighbors.get(*dir) {
-                let mut current_pos = *pos;
-                let mut tile_ent = tile_storage.get(&pos).unwrap();
-                //tile state
-                let mut tile_s = tile_state_q.get_mut(tile_ent).unwrap();
-
-                // checks if the first the closest tile is empty or else
-                // if it has a piece with opposite color of the selection
-                if matches!(tile_s.tile_type, Tile::Empty) {
-                    tile_s.tile_type = Tile::HighLighted;
-                    highlight_tile(commands, grid_size, map_type, &pos, mesh, material);
-
-                    // gets the neighbor which is in the direction specified, and spawns the circle, it
-                    // keeps doing it until there's a piece or it reaches the end
-                    while let Some(n_pos) =
-                        Neighbors::get_square_neighboring_positions(&current_pos, map_size, true)
-                            .get(*dir)
-                    {
-                        tile_ent = tile_storage.get(&n_pos).unwrap();
-                        tile_s = tile_state_q.get_mut(tile_ent).unwrap();
-
-                        // changes the position to be the neighbor's last accessed
-                        current_pos = TilePos {
-                            x: n_pos.x,
-                            y: n_pos.y,
-                        };
-
-                        if let Tile::Empty = tile_s.tile_type {
-                            tile_s.tile_type = Tile::HighLighted;
-                            highlight_tile(commands, grid_size, map_type, &n_pos, mesh, material);
-                        } else if let Some(e) = tile_s.piece_ent {
-                            let piece = piece_type.get(e).unwrap();
-
-                            // checks if it's color is the opposite of the selection's
-                            if piece.get_team() != self.team {
-                                tile_s.tile_type = Tile::HighLighted;
-                                highlight_tile(
-                                    commands, grid_size, map_type, &n_pos, mesh, material,
-                                );
-                            }
-
-                            break;
-                        }
-                    }
-                } else {
-                    let piece = piece_type.get(tile_s.piece_ent.unwrap()).unwrap();
-
-                    if piece.get_team() != self.team {
-                        tile_s.tile_type = Tile::HighLighted;
-                        highlight_tile(commands, grid_size, map_type, &pos, mesh, material);
-                    }
-                }
-            }
-        });
-    }
-}
-
 // helper function to spawn the pieces
 pub fn spawn_piece(
     commands: &mut Commands,
@@ -8,7 +8,19 @@ use bevy_ecs_tilemap::{
     TilemapBundle,
 };
 
-use crate::{bishop, king, knight, pawn, piece::Team, queen, rock, GameAssets};
+use bevy_chess::rules::{PieceKind, Position, Square};
+
+use iyes_loopless::prelude::*;
+
+#[cfg(debug_assertions)]
+use crate::movement::MoveEvent;
+use crate::{
+    bishop,
+    game::{BoardResetEvent, GameState},
+    king, knight, pawn,
+    piece::{HighLight, PieceType},
+    queen, rock, GameAssets,
+};
 
 pub const TILE_SIZE: f32 = 64.0;
 
@@ -25,12 +37,29 @@ pub struct TileState {
     pub piece_ent: Option<Entity>,
 }
 
+// converts a tile of the board into the square the rules engine knows it by
+pub fn to_square(tile_pos: &TilePos) -> Square {
+    Square::new(tile_pos.x as u8, tile_pos.y as u8)
+}
+
+// converts a square of the rules engine back into its tile on the board
+pub fn to_tile_pos(square: Square) -> TilePos {
+    TilePos {
+        x: square.file() as u32,
+        y: square.rank() as u32,
+    }
+}
+
 pub struct BoardPlugin;
 
 impl Plugin for BoardPlugin {
     fn build(&self, app: &mut App) {
         app.add_startup_system(Self::tilemap_builder)
-            .add_startup_system_to_stage(StartupStage::PostStartup, Self::setup_pieces);
+            .add_startup_system_to_stage(StartupStage::PostStartup, Self::setup_pieces)
+            .add_system(Self::respawn_pieces.run_on_event::<BoardResetEvent>());
+
+        #[cfg(debug_assertions)]
+        app.add_system(Self::check_sync.run_on_event::<MoveEvent>());
     }
 }
 
@@ -90,9 +119,11 @@ impl BoardPlugin {
         });
     }
 
-    // Spawn the pieces in their correct positions
+    // Spawn the pieces of the game's starting position, which is the standard layout unless
+    // another one was loaded from a FEN string or a PGN file
     fn setup_pieces(
         mut commands: Commands,
+        game: Res<GameState>,
         game_assets: Res<GameAssets>,
         tile_storage_q: Query<(&TileStorage, &TilemapGridSize, &TilemapType)>,
         mut tile_query: Query<(&TilePos, &mut TileState)>,
@@ -100,253 +131,106 @@ impl BoardPlugin {
         mut material: ResMut<Assets<ColorMaterial>>,
     ) {
         for (tile_storage, grid_size, map_type) in tile_storage_q.iter() {
-            //Blacks
-
-            // spawn black rocks
-            rock::spawn_piece(
-                &mut commands,
-                Team::Black,
-                TilePos { x: 0, y: 7 },
-                tile_storage,
-                &mut tile_query,
-                grid_size,
-                map_type,
-                game_assets.black_rock.clone(),
-                &mut meshes,
-                &mut material,
-            );
-            rock::spawn_piece(
-                &mut commands,
-                Team::Black,
-                TilePos { x: 7, y: 7 },
-                tile_storage,
-                &mut tile_query,
-                grid_size,
-                map_type,
-                game_assets.black_rock.clone(),
-                &mut meshes,
-                &mut material,
-            );
-
-            // spawn black knights
-            knight::spawn_piece(
-                &mut commands,
-                Team::Black,
-                TilePos { x: 1, y: 7 },
-                tile_storage,
-                &mut tile_query,
-                grid_size,
-                map_type,
-                game_assets.black_knight.clone(),
-                &mut meshes,
-                &mut material,
-            );
-            knight::spawn_piece(
+            spawn_pieces(
                 &mut commands,
-                Team::Black,
-                TilePos { x: 6, y: 7 },
+                game.position(),
+                &game_assets,
                 tile_storage,
                 &mut tile_query,
                 grid_size,
                 map_type,
-                game_assets.black_knight.clone(),
-                &mut meshes,
-                &mut material,
-            );
-
-            // spawn black bishops
-            bishop::spawn_piece(
-                &mut commands,
-                Team::Black,
-                TilePos { x: 2, y: 7 },
-                tile_storage,
-                &mut tile_query,
-                grid_size,
-                map_type,
-                game_assets.black_bishop.clone(),
-                &mut meshes,
-                &mut material,
-            );
-            bishop::spawn_piece(
-                &mut commands,
-                Team::Black,
-                TilePos { x: 5, y: 7 },
-                tile_storage,
-                &mut tile_query,
-                grid_size,
-                map_type,
-                game_assets.black_bishop.clone(),
                 &mut meshes,
                 &mut material,
             );
+        }
+    }
 
-            // spawn black queen
-            queen::spawn_piece(
-                &mut commands,
-                Team::Black,
-                TilePos { x: 3, y: 7 },
-                tile_storage,
-                &mut tile_query,
-                grid_size,
-                map_type,
-                game_assets.black_queen.clone(),
-                &mut meshes,
-                &mut material,
-            );
+    // when the game is replaced, by a new one or by taking a move back, captured pieces have
+    // to come back as well, so the board is cleared and spawned again from the new position
+    fn respawn_pieces(
+        mut commands: Commands,
+        game: Res<GameState>,
+        game_assets: Res<GameAssets>,
+        pieces: Query<Entity, With<PieceType>>,
+        highlights: Query<Entity, With<HighLight>>,
+        tile_storage_q: Query<(&TileStorage, &TilemapGridSize, &TilemapType)>,
+        mut tile_query: Query<(&TilePos, &mut TileState)>,
+        mut meshes: ResMut<Assets<Mesh>>,
+        mut material: ResMut<Assets<ColorMaterial>>,
+    ) {
+        for ent in pieces.iter().chain(highlights.iter()) {
+            commands.entity(ent).despawn_recursive();
+        }
+        for (_, mut tile_state) in tile_query.iter_mut() {
+            tile_state.tile_type = Tile::Empty;
+            tile_state.piece_ent = None;
+        }
 
-            // spawn black king
-            king::spawn_piece(
-                &mut commands,
-                Team::Black,
-                TilePos { x: 4, y: 7 },
-                tile_storage,
-                &mut tile_query,
-                grid_size,
-                map_type,
-                game_assets.black_king.clone(),
-                &mut meshes,
-                &mut material,
-            );
+        let (tile_storage, grid_size, map_type) = tile_storage_q.single();
+        spawn_pieces(
+            &mut commands,
+            game.position(),
+            &game_assets,
+            tile_storage,
+            &mut tile_query,
+            grid_size,
+            map_type,
+            &mut meshes,
+            &mut material,
+        );
+    }
 
-            // spawn black pawns
-            for x in 0..8 {
-                pawn::spawn_piece(
-                    &mut commands,
-                    Team::Black,
-                    TilePos { x, y: 6 },
-                    tile_storage,
-                    &mut tile_query,
-                    grid_size,
-                    map_type,
-                    game_assets.black_pawn.clone(),
-                    &mut meshes,
-                    &mut material,
+    // the tiles are only a picture of the rules engine's board, in debug builds every move
+    // checks they still have a piece exactly where the engine does
+    #[cfg(debug_assertions)]
+    fn check_sync(game: Res<GameState>, tile_query: Query<(&TilePos, &TileState)>) {
+        for (tile_pos, tile_state) in tile_query.iter() {
+            let square = to_square(tile_pos);
+            let engine_piece = game.position().board().get(square);
+
+            if tile_state.piece_ent.is_some() != engine_piece.is_some() {
+                warn!(
+                    "tile {} is out of sync, the rules engine has {:?} there",
+                    square, engine_piece
                 );
             }
+        }
+    }
+}
 
-            // WHITES
-
-            // spawn white rocks
-            rock::spawn_piece(
-                &mut commands,
-                Team::White,
-                TilePos { x: 0, y: 0 },
-                tile_storage,
-                &mut tile_query,
-                grid_size,
-                map_type,
-                game_assets.white_rock.clone(),
-                &mut meshes,
-                &mut material,
-            );
-            rock::spawn_piece(
-                &mut commands,
-                Team::White,
-                TilePos { x: 7, y: 0 },
-                tile_storage,
-                &mut tile_query,
-                grid_size,
-                map_type,
-                game_assets.white_rock.clone(),
-                &mut meshes,
-                &mut material,
-            );
-
-            // spawn white knights
-            knight::spawn_piece(
-                &mut commands,
-                Team::White,
-                TilePos { x: 1, y: 0 },
-                tile_storage,
-                &mut tile_query,
-                grid_size,
-                map_type,
-                game_assets.white_knight.clone(),
-                &mut meshes,
-                &mut material,
-            );
-            knight::spawn_piece(
-                &mut commands,
-                Team::White,
-                TilePos { x: 6, y: 0 },
-                tile_storage,
-                &mut tile_query,
-                grid_size,
-                map_type,
-                game_assets.white_knight.clone(),
-                &mut meshes,
-                &mut material,
-            );
-
-            // spawn white bishops
-            bishop::spawn_piece(
-                &mut commands,
-                Team::White,
-                TilePos { x: 2, y: 0 },
-                tile_storage,
-                &mut tile_query,
-                grid_size,
-                map_type,
-                game_assets.white_bishop.clone(),
-                &mut meshes,
-                &mut material,
-            );
-            bishop::spawn_piece(
-                &mut commands,
-                Team::White,
-                TilePos { x: 5, y: 0 },
-                tile_storage,
-                &mut tile_query,
-                grid_size,
-                map_type,
-                game_assets.white_bishop.clone(),
-                &mut meshes,
-                &mut material,
-            );
-
-            // spawn white queen
-            queen::spawn_piece(
-                &mut commands,
-                Team::White,
-                TilePos { x: 3, y: 0 },
-                tile_storage,
-                &mut tile_query,
-                grid_size,
-                map_type,
-                game_assets.white_queen.clone(),
-                &mut meshes,
-                &mut material,
-            );
-
-            // spawn white king
-            king::spawn_piece(
-                &mut commands,
-                Team::White,
-                TilePos { x: 4, y: 0 },
-                tile_storage,
-                &mut tile_query,
-                grid_size,
-                map_type,
-                game_assets.white_king.clone(),
-                &mut meshes,
-                &mut material,
-            );
+// spawns a piece on every tile that holds one in `position`
+fn spawn_pieces(
+    commands: &mut Commands,
+    position: &Position,
+    game_assets: &GameAssets,
+    tile_storage: &TileStorage,
+    tile_query: &mut Query<(&TilePos, &mut TileState)>,
+    grid_size: &TilemapGridSize,
+    map_type: &TilemapType,
+    meshes: &mut Assets<Mesh>,
+    material: &mut Assets<ColorMaterial>,
+) {
+    for (square, piece) in position.board().pieces() {
+        let spawn_piece = match piece.kind {
+            PieceKind::Pawn => pawn::spawn_piece,
+            PieceKind::Rock => rock::spawn_piece,
+            PieceKind::Knight => knight::spawn_piece,
+            PieceKind::Bishop => bishop::spawn_piece,
+            PieceKind::Queen => queen::spawn_piece,
+            PieceKind::King => king::spawn_piece,
+        };
 
-            // spawn white pawns
-            for x in 0..8 {
-                pawn::spawn_piece(
-                    &mut commands,
-                    Team::White,
-                    TilePos { x, y: 1 },
-                    tile_storage,
-                    &mut tile_query,
-                    grid_size,
-                    map_type,
-                    game_assets.white_pawn.clone(),
-                    &mut meshes,
-                    &mut material,
-                );
-            }
-        }
+        spawn_piece(
+            commands,
+            piece.team,
+            to_tile_pos(square),
+            tile_storage,
+            tile_query,
+            grid_size,
+            map_type,
+            game_assets.texture(piece),
+            meshes,
+            material,
+        );
     }
 }
@@ -0,0 +1,197 @@
+use crate::rules::{
+    bishop_attacks, king_attacks, knight_attacks, pawn_attacks, queen_attacks, rock_attacks,
+    squares, PieceKind, Position, Square, Team,
+};
+
+use super::{EvalWeights, Phased};
+
+const FILE_A: u64 = 0x0101_0101_0101_0101;
+
+// middlegame and endgame scores added up separately, from White's point of view
+#[derive(Default)]
+struct Totals {
+    middlegame: i32,
+    endgame: i32,
+}
+
+impl Totals {
+    fn add(&mut self, team: Team, weight: Phased, times: i32) {
+        let sign = match team {
+            Team::White => 1,
+            Team::Black => -1,
+        };
+        self.middlegame += sign * weight.0 * times;
+        self.endgame += sign * weight.1 * times;
+    }
+}
+
+fn file_mask(file: u8) -> u64 {
+    FILE_A << file
+}
+
+// the files on either side of `file`
+fn adjacent_files(file: u8) -> u64 {
+    let mut mask = 0;
+    if file > 0 {
+        mask |= file_mask(file - 1);
+    }
+    if file < 7 {
+        mask |= file_mask(file + 1);
+    }
+    mask
+}
+
+// every square on the ranks `team` still has in front of `square`
+fn ranks_ahead(team: Team, square: Square) -> u64 {
+    match team {
+        Team::White if square.rank() < 7 => !0 << ((square.rank() + 1) * 8),
+        Team::Black => (1 << (square.rank() * 8)) - 1,
+        _ => 0,
+    }
+}
+
+// how far up the board `square` is for `team`, 0 being its own back rank
+fn relative_rank(team: Team, square: Square) -> usize {
+    match team {
+        Team::White => square.rank() as usize,
+        Team::Black => 7 - square.rank() as usize,
+    }
+}
+
+// the tables are written from White's side with a8 first, so White flips the ranks and
+// Black reads them as they are
+fn table_index(team: Team, square: Square) -> usize {
+    let rank = match team {
+        Team::White => 7 - square.rank(),
+        Team::Black => square.rank(),
+    };
+    rank as usize * 8 + square.file() as usize
+}
+
+fn pawn_structure(position: &Position, team: Team, weights: &EvalWeights, totals: &mut Totals) {
+    let board = position.board();
+    let pawns = board.pieces_of(PieceKind::Pawn, team);
+    let enemy_pawns = board.pieces_of(PieceKind::Pawn, team.opponent());
+
+    for file in 0..8 {
+        let on_file = (pawns & file_mask(file)).count_ones() as i32;
+        if on_file > 1 {
+            totals.add(team, weights.doubled_pawn, on_file - 1);
+        }
+    }
+
+    for square in squares(pawns) {
+        let file = square.file();
+        if pawns & adjacent_files(file) == 0 {
+            totals.add(team, weights.isolated_pawn, 1);
+        }
+
+        // nothing can stop it on the way but pieces
+        let in_front = ranks_ahead(team, square) & (file_mask(file) | adjacent_files(file));
+        if enemy_pawns & in_front == 0 {
+            totals.add(team, weights.passed_pawn[relative_rank(team, square)], 1);
+        }
+    }
+}
+
+fn king_safety(
+    position: &Position,
+    team: Team,
+    enemy_attacks: u64,
+    weights: &EvalWeights,
+    totals: &mut Totals,
+) {
+    let board = position.board();
+    let king = match board.king_square(team) {
+        Some(king) => king,
+        None => return,
+    };
+    let pawns = board.pieces_of(PieceKind::Pawn, team);
+    let around = king_attacks(king);
+
+    // the pawns on the three squares just in front of the king
+    let shelter = around & ranks_ahead(team, king) & pawns;
+    totals.add(team, weights.king_shelter, shelter.count_ones() as i32);
+    totals.add(
+        team,
+        weights.king_attacked,
+        (around & enemy_attacks).count_ones() as i32,
+    );
+    if pawns & file_mask(king.file()) == 0 {
+        totals.add(team, weights.king_open_file, 1);
+    }
+}
+
+/// How good `position` is for the side to move, in centipawns.
+///
+/// Material, piece placement, mobility, king safety and the pawn structure are scored once
+/// for the middlegame and once for the endgame, and the two are blended by how much
+/// material is left.
+pub fn evaluate(position: &Position, weights: &EvalWeights) -> i32 {
+    let board = position.board();
+    let occupied = board.occupied();
+    let mut totals = Totals::default();
+    let mut attacks = [0; 2];
+    let mut phase = 0;
+
+    for (square, piece) in board.pieces() {
+        let team = piece.team;
+        let index = table_index(team, square);
+        let tables = &weights.piece_squares;
+        let placement = Phased(
+            tables.middlegame.get(piece.kind)[index],
+            tables.endgame.get(piece.kind)[index],
+        );
+
+        totals.add(team, *weights.material.get(piece.kind), 1);
+        totals.add(team, placement, 1);
+        phase += weights.phase.get(piece.kind);
+
+        let reach = match piece.kind {
+            PieceKind::Pawn => pawn_attacks(team, square),
+            PieceKind::Knight => knight_attacks(square),
+            PieceKind::Bishop => bishop_attacks(square, occupied),
+            PieceKind::Rock => rock_attacks(square, occupied),
+            PieceKind::Queen => queen_attacks(square, occupied),
+            PieceKind::King => king_attacks(square),
+        };
+        attacks[team.index()] |= reach;
+        let moves = (reach & !board.team(team)).count_ones() as i32;
+        totals.add(team, *weights.mobility.get(piece.kind), moves);
+    }
+
+    for team in [Team::White, Team::Black] {
+        pawn_structure(position, team, weights, &mut totals);
+        king_safety(
+            position,
+            team,
+            attacks[team.opponent().index()],
+            weights,
+            &mut totals,
+        );
+    }
+
+    // the phase of the starting position, anything at or above it is all middlegame
+    let full_phase = PieceKind::ALL
+        .iter()
+        .map(|&kind| {
+            let count = match kind {
+                PieceKind::Pawn => 16,
+                PieceKind::Knight | PieceKind::Bishop | PieceKind::Rock => 4,
+                PieceKind::Queen | PieceKind::King => 2,
+            };
+            count * weights.phase.get(kind)
+        })
+        .sum::<i32>();
+    let score = if full_phase > 0 {
+        let phase = phase.min(full_phase);
+        (totals.middlegame * phase + totals.endgame * (full_phase - phase)) / full_phase
+    } else {
+        totals.endgame
+    };
+
+    match position.side_to_move() {
+        Team::White => score,
+        Team::Black => -score,
+    }
+}
@@ -0,0 +1,54 @@
+use std::time::Duration;
+
+// kept back from every move's budget for the time it takes to actually play the move
+const MOVE_OVERHEAD: Duration = Duration::from_millis(30);
+// how many more moves the clock is expected to last when the time control doesn't say
+const EXPECTED_MOVES: u32 = 30;
+
+/// When a search stops deepening. Every limit that's set applies, the first one reached
+/// ends the search; with none set it only stops when told to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SearchLimits {
+    /// Search this many plies deep and no further.
+    pub depth: Option<u32>,
+    /// Stop after visiting this many positions.
+    pub nodes: Option<u64>,
+    /// Stop once this much time has gone by.
+    pub time: Option<Duration>,
+}
+
+impl SearchLimits {
+    pub fn depth(depth: u32) -> Self {
+        Self {
+            depth: Some(depth),
+            ..Self::default()
+        }
+    }
+
+    pub fn nodes(nodes: u64) -> Self {
+        Self {
+            nodes: Some(nodes),
+            ..Self::default()
+        }
+    }
+
+    pub fn time(time: Duration) -> Self {
+        Self {
+            time: Some(time),
+            ..Self::default()
+        }
+    }
+
+    /// The time to spend on one move with `remaining` on the clock, `increment` added after
+    /// every move and `moves_to_go` moves left until the next time control, if there is one.
+    ///
+    /// The clock is spread evenly over the moves still expected, plus most of the
+    /// increment, which comes back anyway.
+    pub fn from_clock(remaining: Duration, increment: Duration, moves_to_go: Option<u32>) -> Self {
+        let usable = remaining.saturating_sub(MOVE_OVERHEAD);
+        let moves = moves_to_go.unwrap_or(EXPECTED_MOVES).max(1);
+        let budget = usable / moves + increment * 3 / 4;
+
+        Self::time(budget.min(usable))
+    }
+}
@@ -0,0 +1,21 @@
+//! A computer player built on the rules engine: a static evaluation of positions and a
+//! search over the legal moves that picks the best one for the side to move.
+
+mod book;
+mod eval;
+mod limits;
+mod ordering;
+mod search;
+mod see;
+mod table;
+mod weights;
+
+pub use book::{Book, BookEntry, BookError, BookSelection, PolyglotKeys};
+pub use eval::evaluate;
+pub use limits::SearchLimits;
+pub use search::{
+    mate_in, search, search_with_limits, search_with_report, SearchResult, SearchStats, MATE,
+};
+pub use see::{exchange_value, see};
+pub use table::{Bound, Entry, TranspositionTable};
+pub use weights::{EvalWeights, PerPiece, Phased, PieceSquareTables, WeightsError};
@@ -0,0 +1,102 @@
+use std::cmp::Reverse;
+
+use crate::rules::{Move, PieceKind, Position};
+
+// the more a search looks at good moves first, the sooner it can stop looking. Moves are
+// tried in this order: the table's move, captures and promotions, killers and then the
+// quiet moves by their history
+const HASH_MOVE: i32 = 1_000_000;
+const CAPTURE: i32 = 100_000;
+const KILLERS: [i32; 2] = [90_000, 80_000];
+const HISTORY_MAX: i32 = 79_999;
+
+// deeper than any search goes
+const MAX_PLY: usize = 128;
+
+// rough values for most valuable victim, least valuable attacker: taking a queen with a pawn
+// comes before taking a pawn with a queen
+fn value(kind: PieceKind) -> i32 {
+    match kind {
+        PieceKind::Pawn => 1,
+        PieceKind::Knight | PieceKind::Bishop => 3,
+        PieceKind::Rock => 5,
+        PieceKind::Queen => 9,
+        PieceKind::King => 10,
+    }
+}
+
+/// The piece `mv` takes, if any.
+pub(super) fn captured(position: &Position, mv: Move) -> Option<PieceKind> {
+    match position.board().get(mv.to) {
+        Some(piece) => Some(piece.kind),
+        None => position.en_passant_capture(mv).map(|_| PieceKind::Pawn),
+    }
+}
+
+// quiet moves neither capture nor promote, only they are remembered as killers and in the
+// history, the others are ordered well enough on their own
+pub(super) fn is_quiet(position: &Position, mv: Move) -> bool {
+    mv.promotion.is_none() && captured(position, mv).is_none()
+}
+
+/// What the search learns about good moves as it goes.
+pub(super) struct MoveOrderer {
+    // per ply, the last two quiet moves that caused a cutoff. Positions at the same ply
+    // tend to be alike, so the same move often refutes them all
+    killers: Vec<[Option<Move>; 2]>,
+    // per team and from/to squares, how many cutoffs quiet moves caused, weighted by depth
+    history: Vec<[[i32; 64]; 64]>,
+}
+
+impl MoveOrderer {
+    pub(super) fn new() -> Self {
+        Self {
+            killers: vec![[None; 2]; MAX_PLY],
+            history: vec![[[0; 64]; 64]; 2],
+        }
+    }
+
+    pub(super) fn order(
+        &self,
+        position: &Position,
+        moves: &mut [Move],
+        hash_move: Option<Move>,
+        ply: usize,
+    ) {
+        let team = position.side_to_move().index();
+        let killers = self.killers.get(ply).copied().unwrap_or_default();
+
+        moves.sort_by_cached_key(|&mv| {
+            let score = if Some(mv) == hash_move {
+                HASH_MOVE
+            } else if !is_quiet(position, mv) {
+                let attacker = position
+                    .board()
+                    .get(mv.from)
+                    .map_or(0, |piece| value(piece.kind));
+                let victim = captured(position, mv).map_or(0, value);
+                let promotion = mv.promotion.map_or(0, value);
+                CAPTURE + 10 * (victim + promotion) - attacker
+            } else if let Some(i) = killers.iter().position(|&killer| killer == Some(mv)) {
+                KILLERS[i]
+            } else {
+                self.history[team][mv.from.index()][mv.to.index()].min(HISTORY_MAX)
+            };
+            Reverse(score)
+        });
+    }
+
+    // `mv`, a quiet move, was good enough for a cutoff `depth` plies from the leaves
+    pub(super) fn cutoff(&mut self, position: &Position, mv: Move, depth: u32, ply: usize) {
+        if let Some(killers) = self.killers.get_mut(ply) {
+            if killers[0] != Some(mv) {
+                killers[1] = killers[0];
+                killers[0] = Some(mv);
+            }
+        }
+
+        let team = position.side_to_move().index();
+        let history = &mut self.history[team][mv.from.index()][mv.to.index()];
+        *history = history.saturating_add((depth * depth) as i32);
+    }
+}
@@ -0,0 +1,429 @@
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Instant,
+};
+
+use crate::rules::{Move, PieceKind, Position};
+
+use super::{
+    evaluate, exchange_value,
+    ordering::{captured, is_quiet, MoveOrderer},
+    see, Bound, Entry, EvalWeights, SearchLimits, TranspositionTable,
+};
+
+/// The score of being checkmated right now, mates further away score a little less so the
+/// quickest one is preferred.
+pub const MATE: i32 = 100_000;
+
+// above any score the search can return
+const INFINITY: i32 = MATE + 1;
+// any score past this is a forced mate
+const MATE_BOUND: i32 = MATE - 1000;
+// what positional gains a capture can bring on top of the material, for delta pruning
+const DELTA_MARGIN: i32 = 200;
+// how deep iterative deepening goes when nothing else stops it
+const MAX_DEPTH: u32 = 64;
+// the clock is only read every this many nodes, a power of two
+const TIME_CHECK_INTERVAL: u64 = 1024;
+
+/// What a search found: the move to play and how good it is for the side to move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchResult {
+    /// `None` when the side to move has no legal moves.
+    pub best_move: Option<Move>,
+    /// In centipawns, or `MATE - plies` when there's a forced mate.
+    pub score: i32,
+    /// The deepest search that got to the end, 0 when even the first one was cut short.
+    pub depth: u32,
+    pub stats: SearchStats,
+}
+
+/// Counters to see how well the search prunes, the fewer nodes for the same depth the
+/// better.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SearchStats {
+    /// How many positions were visited.
+    pub nodes: u64,
+    /// Positions found in the transposition table.
+    pub table_hits: u64,
+    /// Positions where a move was too good for the opponent to allow, so the rest of the
+    /// moves were skipped.
+    pub cutoffs: u64,
+    /// Cutoffs by the first move tried, the more the better the moves are ordered.
+    pub first_move_cutoffs: u64,
+}
+
+/// Looks `depth` plies ahead with negamax alpha-beta and returns the best move found,
+/// judging positions with the default weights and starting from an empty table.
+pub fn search(position: &Position, depth: u32) -> SearchResult {
+    search_with_limits(
+        position,
+        SearchLimits::depth(depth),
+        &EvalWeights::default(),
+        &mut TranspositionTable::default(),
+        &AtomicBool::new(false),
+    )
+}
+
+/// Searches one ply deeper at a time until one of the `limits` is reached or `stop` is set
+/// from another thread.
+///
+/// A search that's cut short halfway through a depth is thrown away, the result is the
+/// one from the last depth that was searched completely. Only when not even the first
+/// depth got to the end is it as good as the moves looked at so far.
+///
+/// What's learnt is kept in `table` for the following searches, it only has to be cleared
+/// for a new game.
+pub fn search_with_limits(
+    position: &Position,
+    limits: SearchLimits,
+    weights: &EvalWeights,
+    table: &mut TranspositionTable,
+    stop: &AtomicBool,
+) -> SearchResult {
+    search_with_report(position, limits, weights, table, stop, |_, _| {})
+}
+
+/// [`search_with_limits`] that hands every depth's result to `report` as soon as it's
+/// searched completely, along with the table holding the line that leads to it.
+pub fn search_with_report(
+    position: &Position,
+    limits: SearchLimits,
+    weights: &EvalWeights,
+    table: &mut TranspositionTable,
+    stop: &AtomicBool,
+    mut report: impl FnMut(&SearchResult, &TranspositionTable),
+) -> SearchResult {
+    let mut searcher = Searcher {
+        weights,
+        table,
+        orderer: MoveOrderer::new(),
+        stop,
+        limits,
+        started: Instant::now(),
+        stats: SearchStats::default(),
+        aborted: false,
+    };
+
+    let mut moves = position.legal_moves();
+    if moves.is_empty() {
+        return SearchResult {
+            best_move: None,
+            score: terminal_score(position, 0),
+            depth: 0,
+            stats: SearchStats::default(),
+        };
+    }
+
+    // what's played when the search is stopped before it has looked at a single move
+    let mut result = SearchResult {
+        best_move: Some(moves[0]),
+        score: 0,
+        depth: 0,
+        stats: SearchStats::default(),
+    };
+    let max_depth = limits.depth.unwrap_or(MAX_DEPTH).clamp(1, MAX_DEPTH);
+
+    for depth in 1..=max_depth {
+        // the best move of the last depth is in the table, it's searched first
+        let hash_move = searcher
+            .table
+            .probe(position.zobrist())
+            .and_then(|entry| entry.best_move);
+        searcher.orderer.order(position, &mut moves, hash_move, 0);
+        let (best_move, score) = searcher.root(position, &moves, depth);
+
+        if searcher.aborted {
+            if let (0, Some(best)) = (result.depth, best_move) {
+                result.best_move = Some(best);
+                result.score = score;
+            }
+            break;
+        }
+        result = SearchResult {
+            best_move,
+            score,
+            depth,
+            stats: searcher.stats,
+        };
+        report(&result, searcher.table);
+
+        // a forced mate doesn't get any better by looking deeper
+        if score.abs() >= MATE_BOUND {
+            break;
+        }
+        // the next depth takes several times as long as this one, there's no point starting
+        // it when it can't finish
+        if let Some(time) = limits.time {
+            if searcher.started.elapsed() > time / 2 {
+                break;
+            }
+        }
+    }
+
+    result.stats = searcher.stats;
+    result
+}
+
+/// In how many moves the side to move mates, or is mated when negative, if `score` is a
+/// forced mate.
+pub fn mate_in(score: i32) -> Option<i32> {
+    if score >= MATE_BOUND {
+        Some((MATE - score + 1) / 2)
+    } else if score <= -MATE_BOUND {
+        Some(-(MATE + score) / 2)
+    } else {
+        None
+    }
+}
+
+// the score of a position without moves, mated or stalemated
+fn terminal_score(position: &Position, ply: i32) -> i32 {
+    if position.in_check() {
+        -MATE + ply
+    } else {
+        0
+    }
+}
+
+// mates are stored in the table as distances from the position rather than from the root,
+// it can be reached at another ply the next time
+fn score_to_table(score: i32, ply: i32) -> i32 {
+    if score >= MATE_BOUND {
+        score + ply
+    } else if score <= -MATE_BOUND {
+        score - ply
+    } else {
+        score
+    }
+}
+
+fn score_from_table(score: i32, ply: i32) -> i32 {
+    if score >= MATE_BOUND {
+        score - ply
+    } else if score <= -MATE_BOUND {
+        score + ply
+    } else {
+        score
+    }
+}
+
+// what stays the same for the whole search
+struct Searcher<'a> {
+    weights: &'a EvalWeights,
+    table: &'a mut TranspositionTable,
+    orderer: MoveOrderer,
+    stop: &'a AtomicBool,
+    limits: SearchLimits,
+    started: Instant,
+    stats: SearchStats,
+    // set once a limit is hit, from then on every score is meaningless
+    aborted: bool,
+}
+
+impl Searcher<'_> {
+    fn should_abort(&mut self) -> bool {
+        if !self.aborted {
+            let nodes = self.stats.nodes;
+            let check_time = nodes & (TIME_CHECK_INTERVAL - 1) == 0;
+
+            self.aborted = self.stop.load(Ordering::Relaxed)
+                || matches!(self.limits.nodes, Some(limit) if nodes >= limit)
+                || matches!(self.limits.time, Some(time)
+                    if check_time && self.started.elapsed() >= time);
+        }
+        self.aborted
+    }
+
+    // one depth of the search from the root, the best move found and its score
+    fn root(&mut self, position: &Position, moves: &[Move], depth: u32) -> (Option<Move>, i32) {
+        let mut best_move = None;
+        let mut alpha = -INFINITY;
+
+        for &mv in moves {
+            let mut next = position.clone();
+            next.apply(mv);
+            let score = -self.negamax(&next, depth - 1, 1, -INFINITY, -alpha);
+
+            // the move that was being searched only has a partial score
+            if self.aborted {
+                break;
+            }
+            if best_move.is_none() || score > alpha {
+                alpha = score;
+                best_move = Some(mv);
+            }
+        }
+
+        if !self.aborted {
+            self.table.store(Entry {
+                key: position.zobrist(),
+                depth,
+                score: alpha,
+                bound: Bound::Exact,
+                best_move,
+            });
+        }
+        (best_move, alpha)
+    }
+
+    // scores are always from the point of view of the side to move, so a child's score is
+    // negated. Once a move is found that's better than what the opponent already has
+    // elsewhere (`beta`), the rest of the moves don't matter
+    fn negamax(
+        &mut self,
+        position: &Position,
+        depth: u32,
+        ply: i32,
+        mut alpha: i32,
+        beta: i32,
+    ) -> i32 {
+        // the position may be in the middle of an exchange, which has to be played out
+        // before it can be judged
+        if depth == 0 {
+            return self.quiescence(position, ply, alpha, beta);
+        }
+
+        self.stats.nodes += 1;
+        // whatever comes back is thrown away by the caller
+        if self.should_abort() {
+            return 0;
+        }
+
+        let key = position.zobrist();
+        let mut hash_move = None;
+        if let Some(entry) = self.table.probe(key) {
+            self.stats.table_hits += 1;
+            hash_move = entry.best_move;
+
+            // searched deep enough before, the score can be used as it is unless it's
+            // only a bound that doesn't settle anything here
+            if entry.depth >= depth {
+                let score = score_from_table(entry.score, ply);
+                match entry.bound {
+                    Bound::Exact => return score.clamp(alpha, beta),
+                    Bound::Lower if score >= beta => return beta,
+                    Bound::Upper if score <= alpha => return alpha,
+                    _ => {}
+                }
+            }
+        }
+
+        let mut moves = position.legal_moves();
+        if moves.is_empty() {
+            return terminal_score(position, ply);
+        }
+
+        self.orderer
+            .order(position, &mut moves, hash_move, ply as usize);
+        let mut best_move = None;
+
+        for (i, mv) in moves.into_iter().enumerate() {
+            let mut next = position.clone();
+            next.apply(mv);
+            let score = -self.negamax(&next, depth - 1, ply + 1, -beta, -alpha);
+
+            if self.aborted {
+                return 0;
+            }
+            if score >= beta {
+                self.stats.cutoffs += 1;
+                if i == 0 {
+                    self.stats.first_move_cutoffs += 1;
+                }
+                if is_quiet(position, mv) {
+                    self.orderer.cutoff(position, mv, depth, ply as usize);
+                }
+                self.store(key, depth, beta, Bound::Lower, Some(mv), ply);
+                return beta;
+            }
+            if score > alpha {
+                alpha = score;
+                best_move = Some(mv);
+            }
+        }
+
+        let bound = match best_move {
+            Some(_) => Bound::Exact,
+            None => Bound::Upper,
+        };
+        self.store(key, depth, alpha, bound, best_move, ply);
+        alpha
+    }
+
+    // only captures and promotions are searched until the position is quiet. The side to
+    // move doesn't have to take anything, so the evaluation as it stands ("standing pat")
+    // is already a lower bound. Out of check at least, in check every move is searched
+    fn quiescence(&mut self, position: &Position, ply: i32, mut alpha: i32, beta: i32) -> i32 {
+        self.stats.nodes += 1;
+        if self.should_abort() {
+            return 0;
+        }
+
+        let mut moves = position.legal_moves();
+        if moves.is_empty() {
+            return terminal_score(position, ply);
+        }
+
+        let in_check = position.in_check();
+        let stand_pat = evaluate(position, self.weights);
+        if !in_check {
+            if stand_pat >= beta {
+                return beta;
+            }
+            alpha = alpha.max(stand_pat);
+            moves.retain(|&mv| !is_quiet(position, mv));
+        }
+        self.orderer.order(position, &mut moves, None, ply as usize);
+
+        for mv in moves {
+            if !in_check {
+                // delta pruning: winning the piece outright still wouldn't get the score
+                // up to what's already guaranteed
+                let best_case = captured(position, mv).map_or(0, exchange_value)
+                    + mv.promotion.map_or(0, |kind| {
+                        exchange_value(kind) - exchange_value(PieceKind::Pawn)
+                    });
+                if stand_pat + best_case + DELTA_MARGIN <= alpha {
+                    continue;
+                }
+                // and captures that lose material once the opponent takes back
+                if see(position, mv) < 0 {
+                    continue;
+                }
+            }
+
+            let mut next = position.clone();
+            next.apply(mv);
+            let score = -self.quiescence(&next, ply + 1, -beta, -alpha);
+
+            if self.aborted {
+                return 0;
+            }
+            if score >= beta {
+                return beta;
+            }
+            alpha = alpha.max(score);
+        }
+
+        alpha
+    }
+
+    fn store(
+        &mut self,
+        key: u64,
+        depth: u32,
+        score: i32,
+        bound: Bound,
+        best_move: Option<Move>,
+        ply: i32,
+    ) {
+        self.table.store(Entry {
+            key,
+            depth,
+            score: score_to_table(score, ply),
+            bound,
+            best_move,
+        });
+    }
+}
@@ -0,0 +1,118 @@
+//! Static exchange evaluation: what a capture wins once every piece that can take on the
+//! same square has had its turn, without searching the rest of the board.
+
+use crate::rules::{
+    bishop_attacks, king_attacks, knight_attacks, pawn_attacks, rock_attacks, Move, PieceKind,
+    Position, Square, Team,
+};
+
+/// A piece's worth in an exchange, in centipawns. The king can't be taken, so it's worth
+/// more than anything it could take.
+pub fn exchange_value(kind: PieceKind) -> i32 {
+    match kind {
+        PieceKind::Pawn => 100,
+        PieceKind::Knight => 320,
+        PieceKind::Bishop => 330,
+        PieceKind::Rock => 500,
+        PieceKind::Queen => 900,
+        PieceKind::King => 20_000,
+    }
+}
+
+// the pieces of either team attacking `square` with only the pieces in `occupied` on the
+// board, so a slider standing behind one that has already taken joins in
+fn attackers(position: &Position, square: Square, occupied: u64) -> u64 {
+    let board = position.board();
+    let of_both = |kind| board.pieces_of(kind, Team::White) | board.pieces_of(kind, Team::Black);
+    let queens = of_both(PieceKind::Queen);
+
+    let attackers = pawn_attacks(Team::Black, square)
+        & board.pieces_of(PieceKind::Pawn, Team::White)
+        | pawn_attacks(Team::White, square) & board.pieces_of(PieceKind::Pawn, Team::Black)
+        | knight_attacks(square) & of_both(PieceKind::Knight)
+        | king_attacks(square) & of_both(PieceKind::King)
+        | rock_attacks(square, occupied) & (of_both(PieceKind::Rock) | queens)
+        | bishop_attacks(square, occupied) & (of_both(PieceKind::Bishop) | queens);
+
+    attackers & occupied
+}
+
+// the kinds from the cheapest to the dearest, `PieceKind::ALL` has the rook before the minor
+// pieces
+const CHEAPEST_FIRST: [PieceKind; 6] = [
+    PieceKind::Pawn,
+    PieceKind::Knight,
+    PieceKind::Bishop,
+    PieceKind::Rock,
+    PieceKind::Queen,
+    PieceKind::King,
+];
+
+// the least valuable of `team`'s pieces among `attackers`
+fn least_valuable(position: &Position, attackers: u64, team: Team) -> Option<(u64, PieceKind)> {
+    CHEAPEST_FIRST.into_iter().find_map(|kind| {
+        let pieces = attackers & position.board().pieces_of(kind, team);
+        // the lowest bit of the set
+        (pieces != 0).then(|| (pieces & pieces.wrapping_neg(), kind))
+    })
+}
+
+/// How much material the side to move comes out with after playing `mv` when both sides
+/// keep taking on the square with their cheapest piece, each stopping as soon as going on
+/// would lose more. Negative for a capture that gives away more than it takes.
+///
+/// Pins aren't looked at, a pinned piece is counted as if it could take.
+pub fn see(position: &Position, mv: Move) -> i32 {
+    let board = position.board();
+    let mover = match board.get(mv.from) {
+        Some(piece) => piece,
+        None => return 0,
+    };
+
+    let mut occupied = board.occupied() & !(1 << mv.from.index());
+    let mut gains = Vec::with_capacity(32);
+    gains.push(match board.get(mv.to) {
+        Some(piece) => exchange_value(piece.kind),
+        None => match position.en_passant_capture(mv) {
+            Some(pawn) => {
+                occupied &= !(1 << pawn.index());
+                exchange_value(PieceKind::Pawn)
+            }
+            None => 0,
+        },
+    });
+
+    // the piece that stands on the square after each capture, and could be taken next
+    let mut on_square = match mv.promotion {
+        Some(kind) => {
+            gains[0] += exchange_value(kind) - exchange_value(PieceKind::Pawn);
+            kind
+        }
+        None => mover.kind,
+    };
+    let mut team = mover.team.opponent();
+
+    loop {
+        let attackers = attackers(position, mv.to, occupied);
+        let (attacker, kind) = match least_valuable(position, attackers, team) {
+            Some(attacker) => attacker,
+            None => break,
+        };
+
+        // taking the piece on the square, after what the last capture won
+        let gain = exchange_value(on_square) - gains[gains.len() - 1];
+        gains.push(gain);
+        occupied &= !attacker;
+        on_square = kind;
+        team = team.opponent();
+    }
+
+    // from the last capture back, each side takes only when it's better than stopping
+    while gains.len() > 1 {
+        let last = gains.pop().unwrap_or_default();
+        let before = gains.len() - 1;
+        gains[before] = -(-gains[before]).max(last);
+    }
+
+    gains[0]
+}
@@ -0,0 +1,126 @@
+use std::mem;
+
+use crate::rules::{Move, Position};
+
+/// How a stored score relates to the position's real score. Searches that cut off early
+/// only learn a bound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    /// The score is the real one.
+    Exact,
+    /// A move was found that's at least this good, the others weren't looked at.
+    Lower,
+    /// None of the moves did better than this.
+    Upper,
+}
+
+/// What an earlier search found out about a position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Entry {
+    /// The position's full Zobrist key, different positions can share a slot.
+    pub key: u64,
+    /// How many plies deep the position was searched.
+    pub depth: u32,
+    pub score: i32,
+    pub bound: Bound,
+    /// The move that scored best or caused the cutoff, searched first next time.
+    pub best_move: Option<Move>,
+}
+
+/// Search results by Zobrist key, so positions reached by different move orders are only
+/// searched once and earlier iterations tell later ones which move to try first.
+///
+/// The table has a fixed size: a slot is picked from the key, and a new entry replaces the
+/// one already there unless that one is for another position and was searched deeper.
+pub struct TranspositionTable {
+    entries: Vec<Option<Entry>>,
+}
+
+impl TranspositionTable {
+    /// A table taking at most `megabytes` of memory. With 0 nothing is ever stored, which
+    /// is useful to measure what the table brings.
+    pub fn new(megabytes: usize) -> Self {
+        let slots = megabytes * 1024 * 1024 / mem::size_of::<Option<Entry>>();
+        // a power of two, so the key can be masked to a slot
+        let slots = match slots {
+            0 => 0,
+            slots => 1 << slots.ilog2(),
+        };
+
+        Self {
+            entries: vec![None; slots],
+        }
+    }
+
+    fn slot(&self, key: u64) -> Option<usize> {
+        match self.entries.len() {
+            0 => None,
+            len => Some(key as usize & (len - 1)),
+        }
+    }
+
+    pub fn probe(&self, key: u64) -> Option<Entry> {
+        let entry = self.entries[self.slot(key)?]?;
+        (entry.key == key).then_some(entry)
+    }
+
+    pub fn store(&mut self, entry: Entry) {
+        let slot = match self.slot(entry.key) {
+            Some(slot) => slot,
+            None => return,
+        };
+
+        let keep = match self.entries[slot] {
+            Some(old) => old.key != entry.key && old.depth > entry.depth,
+            None => false,
+        };
+        if !keep {
+            self.entries[slot] = Some(entry);
+        }
+    }
+
+    /// The line of best moves from `position` as far as the table knows it, up to
+    /// `max_len` moves. It's what the search expects both sides to play.
+    pub fn principal_variation(&self, position: &Position, max_len: usize) -> Vec<Move> {
+        let mut line = Vec::new();
+        let mut position = position.clone();
+
+        while line.len() < max_len {
+            // another position sharing the slot could have left a move that isn't legal here
+            match self
+                .probe(position.zobrist())
+                .and_then(|entry| entry.best_move)
+            {
+                Some(mv) if position.legal_moves().contains(&mv) => {
+                    position.apply(mv);
+                    line.push(mv);
+                }
+                _ => break,
+            }
+        }
+
+        line
+    }
+
+    /// Forgets everything, for a new game.
+    pub fn clear(&mut self) {
+        self.entries.fill(None);
+    }
+
+    /// How full the table is, in thousandths, judging from the first thousand slots.
+    pub fn permill_full(&self) -> usize {
+        self.entries
+            .iter()
+            .take(1000)
+            .filter(|entry| entry.is_some())
+            .count()
+            * 1000
+            / self.entries.len().clamp(1, 1000)
+    }
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        Self::new(16)
+    }
+}
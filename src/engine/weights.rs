@@ -0,0 +1,134 @@
+use std::{fmt, fs, io, path::Path};
+
+use serde::Deserialize;
+
+use crate::rules::PieceKind;
+
+// the weights the engine plays with when no other file is given
+const DEFAULT_WEIGHTS: &str = include_str!("../../assets/eval.ron");
+
+/// A weight for the middlegame and one for the endgame, the evaluation slides from the first
+/// to the second as pieces come off the board.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub struct Phased(pub i32, pub i32);
+
+/// One value for every kind of piece.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct PerPiece<T> {
+    pub pawn: T,
+    pub knight: T,
+    pub bishop: T,
+    pub rock: T,
+    pub queen: T,
+    pub king: T,
+}
+
+impl<T> PerPiece<T> {
+    pub fn get(&self, kind: PieceKind) -> &T {
+        match kind {
+            PieceKind::Pawn => &self.pawn,
+            PieceKind::Knight => &self.knight,
+            PieceKind::Bishop => &self.bishop,
+            PieceKind::Rock => &self.rock,
+            PieceKind::Queen => &self.queen,
+            PieceKind::King => &self.king,
+        }
+    }
+}
+
+/// Bonuses for standing on each square, 64 per piece written as the board is seen from
+/// White's side: a8 first, h1 last. Black's pieces use the tables mirrored.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct PieceSquareTables {
+    pub middlegame: PerPiece<Vec<i32>>,
+    pub endgame: PerPiece<Vec<i32>>,
+}
+
+/// Everything the static evaluation adds up, in centipawns.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct EvalWeights {
+    pub material: PerPiece<Phased>,
+    pub piece_squares: PieceSquareTables,
+    /// Per square a piece reaches that isn't taken by one of its own.
+    pub mobility: PerPiece<Phased>,
+    /// Per own pawn right in front of the king.
+    pub king_shelter: Phased,
+    /// Per square next to the king the opponent attacks.
+    pub king_attacked: Phased,
+    /// For the king's file having no own pawns left.
+    pub king_open_file: Phased,
+    /// Per extra pawn on a file.
+    pub doubled_pawn: Phased,
+    /// Per pawn without own pawns on the files next to it.
+    pub isolated_pawn: Phased,
+    /// Per passed pawn, by how far it got: 8 entries, from its own back rank to the last one.
+    pub passed_pawn: Vec<Phased>,
+    /// How much each piece counts towards the game still being in the middlegame. With all
+    /// the pieces of the starting position on the board it's fully a middlegame, without
+    /// them fully an endgame.
+    pub phase: PerPiece<i32>,
+}
+
+#[derive(Debug)]
+pub enum WeightsError {
+    Io(io::Error),
+    Parse(ron::error::SpannedError),
+    TableSize { table: String, len: usize },
+}
+
+impl fmt::Display for WeightsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "couldn't read the weights: {}", err),
+            Self::Parse(err) => write!(f, "invalid weights: {}", err),
+            Self::TableSize { table, len } => {
+                write!(f, "the {} table has {} entries", table, len)
+            }
+        }
+    }
+}
+
+impl std::error::Error for WeightsError {}
+
+impl EvalWeights {
+    /// Reads the weights from a RON file, see `assets/eval.ron` for the defaults.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, WeightsError> {
+        let ron = fs::read_to_string(path).map_err(WeightsError::Io)?;
+        Self::from_ron(&ron)
+    }
+
+    pub fn from_ron(ron: &str) -> Result<Self, WeightsError> {
+        let weights: Self = ron::from_str(ron).map_err(WeightsError::Parse)?;
+
+        // serde can't check the length of a list, the evaluation indexes them blindly
+        let tables = &weights.piece_squares;
+        for (phase, tables) in [
+            ("middlegame", &tables.middlegame),
+            ("endgame", &tables.endgame),
+        ] {
+            for kind in PieceKind::ALL {
+                let len = tables.get(kind).len();
+                if len != 64 {
+                    return Err(WeightsError::TableSize {
+                        table: format!("{} {:?}", phase, kind).to_lowercase(),
+                        len,
+                    });
+                }
+            }
+        }
+        if weights.passed_pawn.len() != 8 {
+            return Err(WeightsError::TableSize {
+                table: "passed pawn".to_string(),
+                len: weights.passed_pawn.len(),
+            });
+        }
+
+        Ok(weights)
+    }
+}
+
+impl Default for EvalWeights {
+    fn default() -> Self {
+        Self::from_ron(DEFAULT_WEIGHTS).expect("the default weights are valid")
+    }
+}
@@ -0,0 +1,238 @@
+use std::sync::{Arc, Mutex, PoisonError};
+
+use bevy::{
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, Task},
+};
+use bevy_chess::{
+    rules::{Move, Team},
+    uci::{EngineError, EngineProcess, EngineStopper, Go},
+};
+use futures_lite::future;
+use iyes_loopless::prelude::*;
+
+use crate::{
+    game::{game_in_progress, BoardResetEvent, GameState},
+    movement::PlayMoveEvent,
+    promotion::PendingPromotion,
+    replay::Replay,
+};
+
+// another UCI engine, running as a child process, plays this team. It's asked for each
+// move with the game so far and searches as long as `go` says
+#[derive(Resource)]
+pub struct ExternalEngine {
+    pub team: Team,
+    pub go: Go,
+    // shared with the search running in the background
+    process: Arc<Mutex<EngineProcess>>,
+    stopper: EngineStopper,
+}
+
+impl ExternalEngine {
+    pub fn new(team: Team, go: Go, mut process: EngineProcess) -> Result<Self, EngineError> {
+        process.new_game()?;
+
+        Ok(Self {
+            team,
+            go,
+            stopper: process.stopper(),
+            process: Arc::new(Mutex::new(process)),
+        })
+    }
+}
+
+// the engine's search running in the background, for the position with the given key
+#[derive(Resource)]
+pub struct EngineSearch {
+    task: Task<Result<Option<Move>, EngineError>>,
+    zobrist: u64,
+    stopper: EngineStopper,
+}
+
+impl Drop for EngineSearch {
+    // the engine answers `stop` with the move it has so far, which the task reads so the
+    // next search doesn't take it for its own
+    fn drop(&mut self) {
+        let _ = self.stopper.stop();
+    }
+}
+
+pub struct ExternalEnginePlugin;
+
+impl Plugin for ExternalEnginePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            Self::start_search
+                .run_if_resource_exists::<ExternalEngine>()
+                .run_if(game_in_progress)
+                .run_unless_resource_exists::<EngineSearch>()
+                .run_unless_resource_exists::<Replay>()
+                .run_unless_resource_exists::<PendingPromotion>(),
+        )
+        .add_system(Self::poll_search.run_if_resource_exists::<EngineSearch>());
+    }
+}
+
+impl ExternalEnginePlugin {
+    // asks the engine for a move when it's its turn, waiting for the answer on the
+    // background task pool so the frames keep coming
+    fn start_search(
+        mut commands: Commands,
+        engine: Res<ExternalEngine>,
+        game: Res<GameState>,
+        // the position the engine was last asked about, so it isn't asked again before its
+        // move has been played
+        mut searched: Local<Option<(usize, u64)>>,
+        mut board_reset: EventReader<BoardResetEvent>,
+    ) {
+        // a new game can start from the position searched last
+        if board_reset.iter().count() > 0 {
+            *searched = None;
+        }
+        if game.side_to_move() != engine.team {
+            *searched = None;
+            return;
+        }
+
+        let current = (game.game.moves().len(), game.zobrist());
+        if *searched == Some(current) {
+            return;
+        }
+        *searched = Some(current);
+
+        let start = game.game.start().clone();
+        let moves = game.game.moves().to_vec();
+        let go = engine.go.clone();
+        let process = engine.process.clone();
+        let task = AsyncComputeTaskPool::get().spawn(async move {
+            // a cancelled search gives the process back once the engine has stopped
+            let mut process = process.lock().unwrap_or_else(PoisonError::into_inner);
+            process.best_move(&start, &moves, &go)
+        });
+
+        commands.insert_resource(EngineSearch {
+            task,
+            zobrist: game.zobrist(),
+            stopper: engine.stopper.clone(),
+        });
+    }
+
+    // plays the engine's move through the same path as the players' moves. A new game
+    // removes the search before it's done, and so does the game ending or the position
+    // changing under it some other way. An engine that fails is dropped so the game can go
+    // on without it
+    fn poll_search(
+        mut commands: Commands,
+        mut search: ResMut<EngineSearch>,
+        game: Res<GameState>,
+        mut play_move: EventWriter<PlayMoveEvent>,
+    ) {
+        if search.zobrist != game.zobrist() || game.outcome.is_some() {
+            info!("engine search cancelled");
+            commands.remove_resource::<EngineSearch>();
+            return;
+        }
+
+        let result = match future::block_on(future::poll_once(&mut search.task)) {
+            Some(result) => result,
+            None => return,
+        };
+        commands.remove_resource::<EngineSearch>();
+
+        match result {
+            Ok(Some(mv)) => {
+                info!("engine plays {}", game.position().san(mv));
+                play_move.send(PlayMoveEvent(mv));
+            }
+            Ok(None) => info!("engine has no move to play"),
+            Err(err) => {
+                error!("{}, the engine's pieces are left to the players", err);
+                commands.remove_resource::<ExternalEngine>();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        env, thread,
+        time::{Duration, Instant},
+    };
+
+    use bevy::{ecs::event::Events, tasks::TaskPool};
+    use bevy_chess::rules::Position;
+
+    use super::*;
+
+    // the stand-in engine of the tests, answering every `go` with the next of `moves`. Cargo
+    // puts it next to the directory of the test binaries and `cargo test` builds every binary
+    // before running any test
+    fn scripted_engine(moves: &[&str]) -> EngineProcess {
+        let mut path = env::current_exe().unwrap();
+        path.pop();
+        path.pop();
+        path.push(format!("scripted-engine{}", env::consts::EXE_SUFFIX));
+
+        let moves: Vec<String> = moves.iter().map(|mv| mv.to_string()).collect();
+        EngineProcess::spawn(path, &moves).unwrap()
+    }
+
+    // a game with the engine playing white, from the standard starting position
+    fn app(process: EngineProcess) -> App {
+        AsyncComputeTaskPool::init(TaskPool::default);
+        let go = Go {
+            move_time: Some(Duration::from_millis(10)),
+            ..Go::default()
+        };
+
+        let mut app = App::new();
+        app.add_event::<PlayMoveEvent>()
+            .add_event::<BoardResetEvent>()
+            .insert_resource(GameState::default())
+            .insert_resource(ExternalEngine::new(Team::White, go, process).unwrap())
+            .add_plugin(ExternalEnginePlugin);
+
+        app
+    }
+
+    // the moves the app is asked to play until `done`, failing when the engine takes too long
+    fn update_until(app: &mut App, done: impl Fn(&App, &[Move]) -> bool) -> Vec<Move> {
+        let started = Instant::now();
+        let mut reader = app.world.resource::<Events<PlayMoveEvent>>().get_reader();
+        let mut played = Vec::new();
+
+        while !done(app, &played) {
+            assert!(started.elapsed() < Duration::from_secs(10));
+            thread::sleep(Duration::from_millis(1));
+            app.update();
+            let events = app.world.resource::<Events<PlayMoveEvent>>();
+            played.extend(reader.iter(events).map(|PlayMoveEvent(mv)| *mv));
+        }
+
+        played
+    }
+
+    #[test]
+    fn plays_the_engines_move() {
+        let mut app = app(scripted_engine(&["e2e4"]));
+
+        let played = update_until(&mut app, |_, played| !played.is_empty());
+        let e4 = Position::default().parse_uci("e2e4").unwrap();
+        assert_eq!(played, vec![e4]);
+        assert!(app.world.contains_resource::<ExternalEngine>());
+    }
+
+    #[test]
+    fn an_engine_that_fails_is_dropped() {
+        // out of moves, the stand-in quits instead of answering
+        let mut app = app(scripted_engine(&[]));
+
+        let played = update_until(&mut app, |app, _| {
+            !app.world.contains_resource::<ExternalEngine>()
+        });
+        assert!(played.is_empty());
+        assert!(!app.world.contains_resource::<EngineSearch>());
+    }
+}
@@ -0,0 +1,203 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_chess::rules::{EndReason, Game, GameResult, Move, Outcome, Piece, Position, Team};
+use iyes_loopless::prelude::*;
+
+use crate::{
+    ai::AiSearch,
+    external::EngineSearch,
+    movement::MoveEvent,
+    promotion::{PendingPromotion, PromotionPicker},
+    replay::Replay,
+};
+
+// the rules-side model of the game, the pieces on the tilemap mirror its position
+#[derive(Resource, Default)]
+pub struct GameState {
+    pub game: Game,
+    // set once the game has finished, no more moves are accepted after that
+    pub outcome: Option<Outcome>,
+    // how long each move took, for the clock comments of the game record
+    pub move_times: Vec<Duration>,
+    // when the side to move got the turn, as elapsed app time
+    turn_started: Duration,
+}
+
+impl GameState {
+    // a game loaded from a FEN string could be over before the first move
+    pub fn new(game: Game) -> Self {
+        Self {
+            outcome: game.outcome(),
+            game,
+            ..default()
+        }
+    }
+
+    pub fn position(&self) -> &Position {
+        self.game.position()
+    }
+
+    pub fn side_to_move(&self) -> Team {
+        self.game.side_to_move()
+    }
+
+    // the Zobrist key of the position on the board
+    pub fn zobrist(&self) -> u64 {
+        self.position().zobrist()
+    }
+
+    // plays `mv` at `now`, the elapsed app time, to know how long the move took
+    pub fn play(&mut self, mv: Move, now: Duration) -> Option<Piece> {
+        self.move_times.push(now.saturating_sub(self.turn_started));
+        self.turn_started = now;
+        self.game.play(mv)
+    }
+
+    // starts the game over from its first position at `now`, the elapsed app time
+    pub fn restart(&mut self, now: Duration) {
+        *self = Self {
+            turn_started: now,
+            ..Self::new(Game::new(self.game.start().clone()))
+        };
+    }
+}
+
+// the game on the board was replaced by another one, its pieces have to be spawned again
+pub struct BoardResetEvent;
+
+pub struct GameOver {
+    pub result: GameResult,
+    pub reason: EndReason,
+}
+
+// run condition for the systems that let the players move pieces
+pub fn game_in_progress(game: Res<GameState>) -> bool {
+    game.outcome.is_none()
+}
+
+pub struct GamePlugin;
+
+impl Plugin for GamePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameState>()
+            .add_event::<GameOver>()
+            .add_event::<BoardResetEvent>()
+            .add_system(Self::detect_game_over.run_on_event::<MoveEvent>())
+            .add_system(Self::announce_game_over.run_on_event::<GameOver>())
+            .add_system(Self::update_window_title)
+            .add_system(Self::export_fen)
+            .add_system(Self::new_game.run_unless_resource_exists::<Replay>());
+    }
+}
+
+impl GamePlugin {
+    // checks after every move wether the side to move has been mated or stalemated, or the
+    // game is drawn by the fifty-move rule, repetition or lack of material
+    fn detect_game_over(mut game: ResMut<GameState>, mut game_over: EventWriter<GameOver>) {
+        if game.outcome.is_some() {
+            return;
+        }
+
+        if let Some(outcome) = game.game.outcome() {
+            game.outcome = Some(outcome);
+            game_over.send(GameOver {
+                result: outcome.result,
+                reason: outcome.reason,
+            });
+        }
+    }
+
+    fn announce_game_over(mut game_over: EventReader<GameOver>) {
+        for event in game_over.iter() {
+            info!("game over by {}: {}", event.reason, event.result);
+        }
+    }
+
+    // shows whose turn it is in the window title, so hot-seat players know who's next, and
+    // the result once the game is over. While the computer or the engine is searching it says
+    // so
+    fn update_window_title(
+        game: Res<GameState>,
+        search: Option<Res<AiSearch>>,
+        engine_search: Option<Res<EngineSearch>>,
+        mut windows: ResMut<Windows>,
+        mut shown: Local<String>,
+    ) {
+        let mut title = format!("{} - {}", base_title(), status(&game));
+        if search.is_some() || engine_search.is_some() {
+            title.push_str(" - thinking…");
+        }
+        if *shown == title {
+            return;
+        }
+
+        if let Some(window) = windows.get_primary_mut() {
+            window.set_title(title.clone());
+            *shown = title;
+        }
+    }
+
+    // logs the current position as FEN when F is pressed, to paste into bug reports or
+    // other chess tools
+    fn export_fen(game: Res<GameState>, keyboard: Res<Input<KeyCode>>) {
+        if keyboard.just_pressed(KeyCode::F) {
+            info!(
+                "FEN: {} (key {:016x})",
+                game.position().to_fen(),
+                game.zobrist()
+            );
+        }
+    }
+
+    // N starts the game over. Removing a search that's still running stops it, and a
+    // promotion waiting for its piece is given up
+    fn new_game(
+        mut commands: Commands,
+        mut game: ResMut<GameState>,
+        keyboard: Res<Input<KeyCode>>,
+        time: Res<Time>,
+        picker: Query<Entity, With<PromotionPicker>>,
+        mut board_reset: EventWriter<BoardResetEvent>,
+    ) {
+        if !keyboard.just_pressed(KeyCode::N) {
+            return;
+        }
+
+        info!("new game");
+        game.restart(time.elapsed());
+        commands.remove_resource::<AiSearch>();
+        commands.remove_resource::<EngineSearch>();
+        commands.remove_resource::<PendingPromotion>();
+        for ent in picker.iter() {
+            commands.entity(ent).despawn_recursive();
+        }
+        board_reset.send(BoardResetEvent);
+    }
+}
+
+fn status(game: &GameState) -> String {
+    match game.outcome {
+        Some(Outcome {
+            result: GameResult::Draw,
+            reason,
+        }) => format!("Draw by {}", reason),
+        Some(Outcome { result, reason }) => {
+            let winner = match result {
+                GameResult::WhiteWins => Team::White,
+                _ => Team::Black,
+            };
+            format!("{:?} wins by {} ({})", winner, reason, result)
+        }
+        None if game.position().in_check() => format!("{:?} to move (check)", game.side_to_move()),
+        None => format!("{:?} to move", game.side_to_move()),
+    }
+}
+
+pub fn base_title() -> String {
+    format!(
+        "{} - v{}",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION")
+    )
+}
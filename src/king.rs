@@ -1,21 +1,20 @@
 use bevy::{
     prelude::{
-        shape, Assets, Color, Commands, Component, Entity, Handle, Image, Mesh, Name, Query,
-        Transform, Vec2,
+        shape, Assets, Color, Commands, Component, Handle, Image, Mesh, Name, Query, Transform,
+        Vec2,
     },
     sprite::{ColorMaterial, MaterialMesh2dBundle, Mesh2dHandle, Sprite, SpriteBundle},
     utils::default,
 };
 use bevy_ecs_tilemap::{
-    helpers::square_grid::neighbors::Neighbors,
-    prelude::{TilemapGridSize, TilemapSize, TilemapType},
+    prelude::{TilemapGridSize, TilemapType},
     tiles::{TilePos, TileStorage},
 };
 use bevy_mod_picking::PickableBundle;
 
 use crate::{
     board::{Tile, TileState},
-    piece::{highlight_tile, PieceType, Team},
+    piece::{PieceType, Team},
 };
 
 #[derive(Component, Clone, Copy)]
@@ -23,45 +22,6 @@ pub struct King {
     pub team: Team,
 }
 
-impl King {
-    pub fn movement(
-        self,
-        commands: &mut Commands,
-        tile_storage: &TileStorage,
-        tile_pos: TilePos,
-        tile_state_q: &mut Query<&mut TileState>,
-        piece_type: &Query<&PieceType>,
-        grid_size: &TilemapGridSize,
-        map_size: &TilemapSize,
-        map_type: &TilemapType,
-        meshes: &mut Assets<Mesh>,
-        materials: &mut Assets<ColorMaterial>,
-    ) {
-        let neighbors_positions =
-            Neighbors::get_square_neighboring_positions(&tile_pos, map_size, true);
-        let mut neigh_ent: Entity;
-
-        for pos in neighbors_positions.iter() {
-            neigh_ent = tile_storage.get(&pos).unwrap();
-            //tile state
-            let mut tile_s = tile_state_q.get_mut(neigh_ent).unwrap();
-
-            //check wether there is a piece on the tile
-            if let Tile::Empty = tile_s.tile_type {
-                tile_s.tile_type = Tile::HighLighted;
-                highlight_tile(commands, grid_size, map_type, pos, meshes, materials);
-            } else if let Some(e) = tile_s.piece_ent {
-                let piece = piece_type.get(e).unwrap();
-
-                // checks if it's color is the opposite of the selection's
-                if piece.get_team() != self.team {
-                    tile_s.tile_type = Tile::HighLighted;
-                    highlight_tile(commands, grid_size, map_type, pos, meshes, materials);
-                }
-            }
-        }
-    }
-}
 // helper function to spawn the pieces
 pub fn spawn_piece(
     commands: &mut Commands,
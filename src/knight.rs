@@ -14,7 +14,7 @@ use bevy_mod_picking::PickableBundle;
 
 use crate::{
     board::{Tile, TileState},
-    piece::{highlight_tile, PieceType, Team},
+    piece::{PieceType, Team},
 };
 
 #[derive(Component, Clone, Copy)]
@@ -22,68 +22,6 @@ pub struct Knight {
     pub team: Team,
 }
 
-impl Knight {
-    pub fn knight_movement(
-        self,
-        commands: &mut Commands,
-        tile_storage: &TileStorage,
-        tile_pos: TilePos,
-        tile_state_q: &mut Query<&mut TileState>,
-        piece_type: &Query<&PieceType>,
-        grid_size: &TilemapGridSize,
-        map_type: &TilemapType,
-        meshes: &mut Assets<Mesh>,
-        materials: &mut Assets<ColorMaterial>,
-    ) {
-        let directions: [(i32, i32); 8] = [
-            (1, 2),
-            (-1, 2),
-            (1, -2),
-            (-1, -2),
-            (2, 1),
-            (2, -1),
-            (-2, 1),
-            (-2, -1),
-        ];
-
-        for direction in directions.iter() {
-            // get the posible move's position
-            let (x, y) = (
-                tile_pos.x as i32 + direction.0,
-                tile_pos.y as i32 + direction.1,
-            );
-
-            if (x >= 0 && x <= 7) && (y >= 0 && y <= 7) {
-                let new_pos = TilePos {
-                    x: x as u32,
-                    y: y as u32,
-                };
-
-                if let Some(neigh_ent) = tile_storage.get(&new_pos) {
-                    //tile state
-                    let mut tile_s = tile_state_q.get_mut(neigh_ent).unwrap();
-
-                    //check wether there is a piece on the tile
-                    if let Tile::Empty = tile_s.tile_type {
-                        tile_s.tile_type = Tile::HighLighted;
-                        highlight_tile(commands, grid_size, map_type, &new_pos, meshes, materials);
-                    } else if let Some(e) = tile_s.piece_ent {
-                        // checks if it's color is the opposite of the selection's
-                        let piece = piece_type.get(e).unwrap();
-
-                        if piece.get_team() != self.team {
-                            tile_s.tile_type = Tile::HighLighted;
-                            highlight_tile(
-                                commands, grid_size, map_type, &new_pos, meshes, materials,
-                            );
-                        }
-                    }
-                }
-            }
-        }
-    }
-}
-
 // helper function to spawn the pieces
 pub fn spawn_piece(
     commands: &mut Commands,
@@ -0,0 +1,6 @@
+//! Headless chess rules, computer player and engine protocols shared by the Bevy game and
+//! any tooling built around it.
+pub mod engine;
+pub mod rules;
+pub mod uci;
+pub mod xboard;
@@ -1,20 +1,44 @@
 #![doc = include_str!("../README.md")]
+// bevy systems take their resources and queries as arguments
+#![allow(clippy::too_many_arguments)]
+use std::{
+    fmt,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
 use bevy::prelude::*;
 use bevy_ecs_tilemap::TilemapPlugin;
 use bevy_inspector_egui::WorldInspectorPlugin;
 use bevy_mod_picking::{DefaultPickingPlugins, PickingCameraBundle};
 
+mod ai;
 mod bishop;
 mod board;
+mod external;
+mod game;
 mod king;
 mod knight;
 mod movement;
 mod pawn;
+mod pgn;
 mod piece;
+mod promotion;
 mod queen;
+mod replay;
 mod rock;
+use ai::{AiBook, AiClock, AiPlayer, AiPlugin};
+use bevy_chess::engine::{Book, EvalWeights, PolyglotKeys, SearchLimits, TranspositionTable};
+use bevy_chess::rules::{Game, Pgn, Piece, PieceKind, Position, Team};
+use bevy_chess::uci::{EngineProcess, Go};
 use board::{BoardPlugin, TILE_SIZE};
+use external::{ExternalEngine, ExternalEnginePlugin};
+use game::{base_title, GamePlugin, GameState};
+use pgn::{PgnPlugin, PgnSettings};
 use piece::PiecePlugin;
+use promotion::{PromotionPlugin, PromotionSettings};
+use replay::{Replay, ReplayPlugin};
 
 const WIDTH: f32 = 1024.0;
 const HEIGHT: f32 = 612.0;
@@ -36,18 +60,120 @@ pub struct GameAssets {
     black_king: Handle<Image>,
 }
 
+impl GameAssets {
+    // the sprite drawn for a piece
+    pub fn texture(&self, piece: Piece) -> Handle<Image> {
+        let texture = match (piece.team, piece.kind) {
+            (Team::White, PieceKind::Pawn) => &self.white_pawn,
+            (Team::White, PieceKind::Rock) => &self.white_rock,
+            (Team::White, PieceKind::Knight) => &self.white_knight,
+            (Team::White, PieceKind::Bishop) => &self.white_bishop,
+            (Team::White, PieceKind::Queen) => &self.white_queen,
+            (Team::White, PieceKind::King) => &self.white_king,
+            (Team::Black, PieceKind::Pawn) => &self.black_pawn,
+            (Team::Black, PieceKind::Rock) => &self.black_rock,
+            (Team::Black, PieceKind::Knight) => &self.black_knight,
+            (Team::Black, PieceKind::Bishop) => &self.black_bishop,
+            (Team::Black, PieceKind::Queen) => &self.black_queen,
+            (Team::Black, PieceKind::King) => &self.black_king,
+        };
+
+        texture.clone()
+    }
+}
+
 fn main() {
-    App::new()
-        .insert_resource(ClearColor(BACKGROUND_COLOR))
+    let mut app = App::new();
+
+    // `--replay <file>` steps through a recorded game instead of starting a new one
+    match arg_value("--replay") {
+        Some(path) => {
+            let replay = Replay(load_pgn(&path));
+            app.insert_resource(replay.game_at(0))
+                .insert_resource(replay);
+        }
+        None => {
+            app.insert_resource(GameState::new(Game::new(starting_position())));
+        }
+    }
+
+    // `--ai <white|black>` lets the computer play that team, `--eval <file>` loads the
+    // weights it judges positions with and `--ai-hash <megabytes>` sizes the table of the
+    // positions it searched. It plays on a clock of `--ai-clock <minutes>` plus
+    // `--ai-increment <seconds>` a move, unless it's held to `--ai-depth <plies>` or
+    // `--ai-nodes <count>`. With `--ai-book <file>` it opens from a Polyglot book
+    let ai_team = team_arg_value("--ai");
+    if let Some(team) = ai_team {
+        let limits = SearchLimits {
+            depth: parsed_arg_value("--ai-depth"),
+            nodes: parsed_arg_value("--ai-nodes"),
+            time: None,
+        };
+        let clock = (limits == SearchLimits::default()).then(|| AiClock {
+            time: Duration::from_secs_f64(parsed_arg_value("--ai-clock").unwrap_or(5.0) * 60.0),
+            increment: Duration::from_secs_f64(parsed_arg_value("--ai-increment").unwrap_or(3.0)),
+        });
+        let weights = match arg_value("--eval").map(EvalWeights::load) {
+            Some(Ok(weights)) => weights,
+            Some(Err(err)) => {
+                eprintln!("invalid --eval: {}", err);
+                std::process::exit(2);
+            }
+            None => EvalWeights::default(),
+        };
+        app.insert_resource(AiPlayer {
+            team,
+            limits,
+            clock,
+            weights: Arc::new(weights),
+            table: Arc::new(Mutex::new(TranspositionTable::new(
+                parsed_arg_value("--ai-hash").unwrap_or(16),
+            ))),
+            book: ai_book(),
+        });
+    }
+
+    // `--engine <program>` lets another UCI engine play `--engine-team <white|black>`, black
+    // unless told otherwise, started with `--engine-args "<args>"` and given
+    // `--engine-movetime <milliseconds>` a move
+    if let Some(program) = arg_value("--engine") {
+        let team = team_arg_value("--engine-team").unwrap_or(Team::Black);
+        if ai_team == Some(team) {
+            eprintln!("--ai and --engine can't both play {:?}", team);
+            std::process::exit(2);
+        }
+        let args: Vec<String> = arg_value("--engine-args")
+            .map(|args| args.split_whitespace().map(str::to_string).collect())
+            .unwrap_or_default();
+        let go = Go {
+            move_time: Some(Duration::from_millis(
+                parsed_arg_value("--engine-movetime").unwrap_or(1000),
+            )),
+            ..Go::default()
+        };
+        let engine = EngineProcess::spawn(&program, &args)
+            .and_then(|process| ExternalEngine::new(team, go, process))
+            .unwrap_or_else(|err| {
+                eprintln!("couldn't start --engine {:?}: {}", program, err);
+                std::process::exit(2);
+            });
+        app.insert_resource(engine);
+    }
+
+    app.insert_resource(ClearColor(BACKGROUND_COLOR))
+        .insert_resource(PgnSettings {
+            path: arg_value("--pgn")
+                .unwrap_or_else(|| "game.pgn".to_string())
+                .into(),
+        })
+        .insert_resource(PromotionSettings {
+            auto_queen: std::env::args().any(|arg| arg == "--auto-queen"),
+        })
         .add_plugins(
             DefaultPlugins
                 .set(WindowPlugin {
                     window: WindowDescriptor {
-                        title: format!(
-                            "{} - v{}",
-                            env!("CARGO_PKG_NAME"),
-                            env!("CARGO_PKG_VERSION")
-                        ),
+                        title: base_title(),
                         width: WIDTH,
                         height: HEIGHT,
                         position: WindowPosition::Centered,
@@ -64,11 +190,97 @@ fn main() {
         // Systems
         .add_startup_system(spawn_camera)
         .add_startup_system_to_stage(StartupStage::PreStartup, asset_loader)
+        .add_plugin(GamePlugin)
+        .add_plugin(PromotionPlugin)
+        .add_plugin(PgnPlugin)
+        .add_plugin(ReplayPlugin)
+        .add_plugin(AiPlugin)
+        .add_plugin(ExternalEnginePlugin)
         .add_plugin(BoardPlugin)
         .add_plugin(PiecePlugin)
         .run();
 }
 
+// the value given to a command-line flag, as in `--flag value`
+fn arg_value(flag: &str) -> Option<String> {
+    std::env::args().skip_while(|arg| arg != flag).nth(1)
+}
+
+// `arg_value` parsed, exits when the value isn't valid
+fn parsed_arg_value<T>(flag: &str) -> Option<T>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    arg_value(flag).map(|value| {
+        value.parse().unwrap_or_else(|err| {
+            eprintln!("invalid {} {:?}: {}", flag, value, err);
+            std::process::exit(2);
+        })
+    })
+}
+
+// `--ai-book <file>` is a Polyglot book the computer plays from for the first
+// `--ai-book-plies <plies>` of the game, picking its `--ai-book-select <best|weighted>`
+// moves. Its keys are Polyglot's, or come from the random numbers in `--ai-book-keys <file>`
+fn ai_book() -> Option<AiBook> {
+    let path = arg_value("--ai-book")?;
+
+    let keys = match arg_value("--ai-book-keys") {
+        Some(keys_path) => PolyglotKeys::load(&keys_path).unwrap_or_else(|err| {
+            eprintln!("invalid --ai-book-keys {}: {}", keys_path, err);
+            std::process::exit(2);
+        }),
+        None => PolyglotKeys::default(),
+    };
+    let book = Book::load(&path).unwrap_or_else(|err| {
+        eprintln!("invalid --ai-book {}: {}", path, err);
+        std::process::exit(2);
+    });
+
+    Some(AiBook {
+        book,
+        keys,
+        selection: parsed_arg_value("--ai-book-select").unwrap_or_default(),
+        plies: parsed_arg_value("--ai-book-plies").unwrap_or(20),
+    })
+}
+
+// the team given to a command-line flag, exits when it isn't white or black
+fn team_arg_value(flag: &str) -> Option<Team> {
+    arg_value(flag).map(|team| match team.as_str() {
+        "white" => Team::White,
+        "black" => Team::Black,
+        _ => {
+            eprintln!("{} takes white or black, not {:?}", flag, team);
+            std::process::exit(2);
+        }
+    })
+}
+
+// `--fen "<fen>"` starts the game from the given position instead of the standard one
+fn starting_position() -> Position {
+    match arg_value("--fen") {
+        Some(fen) => Position::from_fen(&fen).unwrap_or_else(|err| {
+            eprintln!("invalid --fen {:?}: {}", fen, err);
+            std::process::exit(2);
+        }),
+        None => Position::default(),
+    }
+}
+
+fn load_pgn(path: &str) -> Pgn {
+    let pgn = std::fs::read_to_string(path).unwrap_or_else(|err| {
+        eprintln!("couldn't read {}: {}", path, err);
+        std::process::exit(2);
+    });
+
+    Pgn::parse(&pgn).unwrap_or_else(|err| {
+        eprintln!("invalid game in {}: {}", path, err);
+        std::process::exit(2);
+    })
+}
+
 fn spawn_camera(mut commands: Commands) {
     commands.spawn((
         Camera2dBundle {
@@ -1,10 +1,11 @@
 use bevy::{
     prelude::{
-        info, Assets, Changed, Commands, Entity, EventReader, EventWriter, Mesh, Query, ResMut,
-        Transform, Vec2, Vec3, With,
+        info, warn, Assets, Changed, Commands, Entity, EventReader, EventWriter, Handle, Image,
+        Mesh, Query, Res, ResMut, Time, Transform, Vec2, Vec3, With,
     },
     sprite::ColorMaterial,
 };
+use bevy_chess::{engine::see, rules::Move};
 use bevy_ecs_tilemap::{
     prelude::{TilemapGridSize, TilemapSize, TilemapType},
     tiles::{TilePos, TileStorage},
@@ -12,194 +13,272 @@ use bevy_ecs_tilemap::{
 use bevy_mod_picking::{PickingEvent, Selection, SelectionEvent};
 
 use crate::{
-    board::{Tile, TileState},
-    piece::{HighLight, PieceDeathEvent, PieceType},
+    board::{to_square, to_tile_pos, Tile, TileState},
+    game::GameState,
+    piece::{highlight_tile, HighLight, PieceDeathEvent, PieceType},
+    promotion::{spawn_promotion_picker, PendingPromotion, PromotionSettings},
+    GameAssets,
 };
 
 pub struct MoveEvent;
 
+// asks for a move to be played on the board
+pub struct PlayMoveEvent(pub Move);
+
 // detects wether a piece has been selected and shows, with a circle, where the player can move
-// the piece to, depending on it's type
+// the piece to, as told by the rules engine
 pub fn get_piece_movements(
     mut commands: Commands,
     mut events: EventReader<PickingEvent>,
+    game: Res<GameState>,
     mut tile_state_q: Query<&mut TileState>,
     piece_type: Query<&PieceType>,
     tile_storage_q: Query<(&TileStorage, &TilemapGridSize, &TilemapSize, &TilemapType)>,
-    transform_q: Query<&mut Transform>,
+    transform_q: Query<&Transform>,
     highlight_pos: Query<Entity, With<HighLight>>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
 ) {
     for event in events.iter() {
         let (tile_storage, grid_size, map_size, map_type) = tile_storage_q.single();
-        if highlight_pos.is_empty() {
-            if let PickingEvent::Selection(e) = event {
-                if let SelectionEvent::JustSelected(s) = e {
-                    if let Ok(piece_t) = piece_type.get(*s) {
-                        //get the cursor position, if it is on the window
-                        if let Ok(t) = transform_q.get(*s) {
-                            let pos = Vec2::new(t.translation.x, t.translation.y);
-                            // gets the position of tile selected by the player
-                            let tile_pos =
-                                TilePos::from_world_pos(&pos, map_size, grid_size, map_type)
-                                    .unwrap();
-                            match piece_t {
-                                PieceType::Rock(r) => r.movement(
-                                    &mut commands,
-                                    tile_storage,
-                                    grid_size,
-                                    map_size,
-                                    map_type,
-                                    &mut tile_state_q,
-                                    &piece_type,
-                                    tile_pos,
-                                    &mut meshes,
-                                    &mut materials,
-                                ),
-                                PieceType::Knight(kn) => kn.knight_movement(
-                                    &mut commands,
-                                    tile_storage,
-                                    tile_pos,
-                                    &mut tile_state_q,
-                                    &piece_type,
-                                    grid_size,
-                                    map_type,
-                                    &mut meshes,
-                                    &mut materials,
-                                ),
-                                PieceType::Bishop(b) => b.movement(
-                                    &mut commands,
-                                    tile_storage,
-                                    grid_size,
-                                    map_size,
-                                    map_type,
-                                    &mut tile_state_q,
-                                    &piece_type,
-                                    tile_pos,
-                                    &mut meshes,
-                                    &mut materials,
-                                ),
-                                PieceType::Queen(q) => q.movement(
-                                    &mut commands,
-                                    tile_storage,
-                                    grid_size,
-                                    map_size,
-                                    map_type,
-                                    &mut tile_state_q,
-                                    &piece_type,
-                                    tile_pos,
-                                    &mut meshes,
-                                    &mut materials,
-                                ),
-                                PieceType::King(k) => k.movement(
-                                    &mut commands,
-                                    tile_storage,
-                                    tile_pos,
-                                    &mut tile_state_q,
-                                    &piece_type,
-                                    grid_size,
-                                    map_size,
-                                    map_type,
-                                    &mut meshes,
-                                    &mut materials,
-                                ),
-                                PieceType::Pawn(p) => p.movement(
-                                    &mut commands,
-                                    tile_pos,
-                                    tile_storage,
-                                    &mut tile_state_q,
-                                    &piece_type,
-                                    grid_size,
-                                    map_size,
-                                    map_type,
-                                    &mut meshes,
-                                    &mut materials,
-                                ),
-                            }
-                        }
-                    }
+        if !highlight_pos.is_empty() {
+            continue;
+        }
+
+        if let PickingEvent::Selection(SelectionEvent::JustSelected(s)) = event {
+            // only the pieces of the side to move can be picked up
+            match piece_type.get(*s) {
+                Ok(piece_t) if piece_t.get_team() == game.side_to_move() => {}
+                _ => continue,
+            }
+
+            //get the cursor position, if it is on the window
+            if let Ok(t) = transform_q.get(*s) {
+                let pos = Vec2::new(t.translation.x, t.translation.y);
+                // gets the position of tile selected by the player
+                let tile_pos =
+                    TilePos::from_world_pos(&pos, map_size, grid_size, map_type).unwrap();
+
+                let position = game.position();
+                for mv in position.moves_from(to_square(&tile_pos)) {
+                    let target = to_tile_pos(mv.to);
+                    // a capture the static exchange says loses material is shown apart
+                    let capture = position.board().get(mv.to).is_some()
+                        || position.en_passant_capture(mv).is_some();
+                    let risky = capture && see(position, mv) < 0;
+                    let mut tile_s = tile_state_q
+                        .get_mut(tile_storage.get(&target).unwrap())
+                        .unwrap();
+
+                    tile_s.tile_type = Tile::HighLighted;
+                    highlight_tile(
+                        &mut commands,
+                        grid_size,
+                        map_type,
+                        &target,
+                        risky,
+                        &mut meshes,
+                        &mut materials,
+                    );
                 }
             }
         }
     }
 }
 
+// once a highlighted tile is picked, asks for the matching move to be played. Promotions wait
+// for the player to choose the new piece unless auto-queen is on
 pub fn move_piece(
-    mut _commands: Commands,
+    mut commands: Commands,
     mut events: EventReader<PickingEvent>,
-    mut tile_state_q: Query<&mut TileState>,
-    mut transform_q: Query<&mut Transform>,
+    game: Res<GameState>,
+    game_assets: Res<GameAssets>,
+    promotion_settings: Res<PromotionSettings>,
+    tile_state_q: Query<&TileState>,
+    transform_q: Query<&Transform>,
     tile_storage_q: Query<(&TileStorage, &TilemapGridSize, &TilemapSize, &TilemapType)>,
     selected_pos: Query<Entity, Changed<Selection>>,
     mut move_event: EventWriter<MoveEvent>,
-    mut death_event: EventWriter<PieceDeathEvent>,
+    mut play_move: EventWriter<PlayMoveEvent>,
 ) {
     for event in events.iter() {
-        if let PickingEvent::Selection(e) = event {
-            if let SelectionEvent::JustDeselected(s) = e {
-                let (tile_storage, grid_size, map_size, map_type) = tile_storage_q.single();
-
-                //get the entity of the selected circle
-                for selection in selected_pos.iter() {
-                    //get the transform of the selected circle
-                    if let Ok(transform_s) = transform_q.get(selection) {
-                        // convert the transform into a 2d vec
-                        let pos = Vec2::new(transform_s.translation.x, transform_s.translation.y);
-                        // get the position of tile selected by the player
-                        let tile_pos =
-                            TilePos::from_world_pos(&pos, map_size, grid_size, map_type).unwrap();
-                        info!("{:?}", tile_pos);
-
-                        // checks wether the movement is correct
-                        if let Tile::HighLighted = tile_state_q
-                            .get_mut(tile_storage.get(&tile_pos).unwrap())
-                            .unwrap()
-                            .tile_type
+        if let PickingEvent::Selection(SelectionEvent::JustDeselected(s)) = event {
+            let (tile_storage, grid_size, map_size, map_type) = tile_storage_q.single();
+
+            //get the entity of the selected circle
+            for selection in selected_pos.iter() {
+                //get the transform of the selected circle
+                if let Ok(transform_s) = transform_q.get(selection) {
+                    // convert the transform into a 2d vec
+                    let pos = Vec2::new(transform_s.translation.x, transform_s.translation.y);
+                    // get the position of tile selected by the player
+                    let tile_pos =
+                        TilePos::from_world_pos(&pos, map_size, grid_size, map_type).unwrap();
+                    info!("{:?}", tile_pos);
+
+                    // checks wether the movement is correct
+                    if let Tile::HighLighted = tile_state_q
+                        .get(tile_storage.get(&tile_pos).unwrap())
+                        .unwrap()
+                        .tile_type
+                    {
+                        // get the old tile position from the selected piece's transform
+                        let selection_t = transform_q.get(*s).unwrap();
+                        let old_tile = TilePos::from_world_pos(
+                            &Vec2::new(selection_t.translation.x, selection_t.translation.y),
+                            map_size,
+                            grid_size,
+                            map_type,
+                        )
+                        .unwrap();
+
+                        // find the move the rules engine generated for this tile, moves of the
+                        // side that isn't on turn are never among them. Promotions come first
+                        // to a queen
+                        let (from, to) = (to_square(&old_tile), to_square(&tile_pos));
+                        let mv = match game
+                            .position()
+                            .legal_moves()
+                            .into_iter()
+                            .find(|mv| mv.from == from && mv.to == to)
                         {
-                            // gets the reference to the selection's transform to be changed
-                            let mut selection_t = transform_q.get_mut(*s).unwrap();
-                            // converts the tile position into the transform which is at the
-                            // center of the selected tile
-                            let new_pos = tile_pos.center_in_world(grid_size, map_type);
-                            // get the old tile position
-                            let old_tile = TilePos::from_world_pos(
-                                &Vec2::new(selection_t.translation.x, selection_t.translation.y),
-                                map_size,
-                                grid_size,
-                                map_type,
-                            )
-                            .unwrap();
-
-                            //get the old tile state and change its type to empty
-                            let mut tile_s = tile_state_q
-                                .get_mut(tile_storage.get(&old_tile).unwrap())
-                                .unwrap();
-                            let piece = tile_s.piece_ent.unwrap();
-
-                            tile_s.tile_type = Tile::Empty;
-                            tile_s.piece_ent = None;
-
-                            //get the selected tile state and change its type to empty
-                            tile_s = tile_state_q
-                                .get_mut(tile_storage.get(&tile_pos).unwrap())
-                                .unwrap();
-
-                            // if theres some piece on the tile just selected, send a death event
-                            if let Some(e) = tile_s.piece_ent {
-                                death_event.send(PieceDeathEvent(e));
-                            }
-
-                            tile_s.tile_type = Tile::NotEmpty;
-                            tile_s.piece_ent = Some(piece);
-
-                            selection_t.translation = Vec3::new(new_pos.x, new_pos.y, 1.0);
+                            Some(mv) => mv,
+                            None => continue,
+                        };
+
+                        if mv.promotion.is_some() && !promotion_settings.auto_queen {
+                            commands.insert_resource(PendingPromotion(Move::new(from, to)));
+                            spawn_promotion_picker(
+                                &mut commands,
+                                &game_assets,
+                                game.side_to_move(),
+                            );
+                        } else {
+                            play_move.send(PlayMoveEvent(mv));
                         }
                     }
                 }
+            }
+
+            move_event.send(MoveEvent)
+        }
+    }
+}
+
+// plays a move on the rules engine and mirrors it on the board entities. Every move goes
+// through here, whoever decided it
+pub fn play_move(
+    mut play_events: EventReader<PlayMoveEvent>,
+    mut game: ResMut<GameState>,
+    game_assets: Res<GameAssets>,
+    time: Res<Time>,
+    mut tile_state_q: Query<&mut TileState>,
+    mut transform_q: Query<&mut Transform>,
+    mut piece_q: Query<(&mut PieceType, &mut Handle<Image>)>,
+    tile_storage_q: Query<(&TileStorage, &TilemapGridSize, &TilemapType)>,
+    mut move_event: EventWriter<MoveEvent>,
+    mut death_event: EventWriter<PieceDeathEvent>,
+) {
+    let (tile_storage, grid_size, map_type) = tile_storage_q.single();
+
+    for PlayMoveEvent(mv) in play_events.iter() {
+        let mv = *mv;
+        if !game.position().legal_moves().contains(&mv) {
+            warn!("ignoring illegal move {}", mv);
+            continue;
+        }
 
-                move_event.send(MoveEvent)
+        let rook_move = game.position().castling_rook(mv);
+        let en_passant = game.position().en_passant_capture(mv);
+        info!("{}", game.position().san(mv));
+        game.play(mv, time.elapsed());
+
+        // if theres some piece on the destination tile, send a death event
+        if let Some(e) = tile_state_q
+            .get(tile_storage.get(&to_tile_pos(mv.to)).unwrap())
+            .unwrap()
+            .piece_ent
+        {
+            death_event.send(PieceDeathEvent(e));
+        }
+
+        // a pawn taken en passant stands beside the capturing pawn's tile
+        if let Some(bypassed) = en_passant {
+            let mut tile_s = tile_state_q
+                .get_mut(tile_storage.get(&to_tile_pos(bypassed)).unwrap())
+                .unwrap();
+
+            if let Some(e) = tile_s.piece_ent.take() {
+                death_event.send(PieceDeathEvent(e));
             }
+            tile_s.tile_type = Tile::Empty;
+        }
+
+        let piece = relocate_piece(
+            &mut tile_state_q,
+            &mut transform_q,
+            tile_storage,
+            grid_size,
+            map_type,
+            mv,
+        );
+
+        // castling takes the rook to the other side of the king as well
+        if let Some(rook_move) = rook_move {
+            relocate_piece(
+                &mut tile_state_q,
+                &mut transform_q,
+                tile_storage,
+                grid_size,
+                map_type,
+                rook_move,
+            );
+        }
+
+        // a promoted pawn keeps its entity but becomes the chosen piece
+        if mv.promotion.is_some() {
+            let promoted = game.position().board().get(mv.to).unwrap();
+            let (mut piece_t, mut texture) = piece_q.get_mut(piece).unwrap();
+
+            *piece_t = PieceType::from(promoted);
+            *texture = game_assets.texture(promoted);
         }
+
+        move_event.send(MoveEvent);
     }
 }
+
+// moves the piece entity standing on the move's origin tile to its destination, keeping the
+// state of both tiles in sync with it
+fn relocate_piece(
+    tile_state_q: &mut Query<&mut TileState>,
+    transform_q: &mut Query<&mut Transform>,
+    tile_storage: &TileStorage,
+    grid_size: &TilemapGridSize,
+    map_type: &TilemapType,
+    mv: Move,
+) -> Entity {
+    let (from, to) = (to_tile_pos(mv.from), to_tile_pos(mv.to));
+
+    //get the old tile state and change its type to empty
+    let mut tile_s = tile_state_q
+        .get_mut(tile_storage.get(&from).unwrap())
+        .unwrap();
+    let piece = tile_s.piece_ent.unwrap();
+
+    tile_s.tile_type = Tile::Empty;
+    tile_s.piece_ent = None;
+
+    //get the new tile state and place the piece on it
+    tile_s = tile_state_q
+        .get_mut(tile_storage.get(&to).unwrap())
+        .unwrap();
+    tile_s.tile_type = Tile::NotEmpty;
+    tile_s.piece_ent = Some(piece);
+
+    // converts the tile position into the transform which is at the center of the tile
+    let new_pos = to.center_in_world(grid_size, map_type);
+    transform_q.get_mut(piece).unwrap().translation = Vec3::new(new_pos.x, new_pos.y, 1.0);
+
+    piece
+}
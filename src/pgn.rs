@@ -0,0 +1,107 @@
+use std::{
+    fs,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::prelude::*;
+use bevy_chess::rules::{Pgn, PgnTags};
+use iyes_loopless::prelude::*;
+
+use crate::{
+    game::{GameOver, GameState},
+    replay::Replay,
+};
+
+#[derive(Resource)]
+pub struct PgnSettings {
+    // where the game record is written, it's overwritten every time the game is saved
+    pub path: PathBuf,
+}
+
+impl Default for PgnSettings {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::from("game.pgn"),
+        }
+    }
+}
+
+pub struct PgnPlugin;
+
+impl Plugin for PgnPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PgnSettings>()
+            .add_system(Self::save_on_request)
+            // reaching the end of a replayed game doesn't overwrite the record
+            .add_system(
+                Self::save_at_game_end
+                    .run_on_event::<GameOver>()
+                    .run_unless_resource_exists::<Replay>(),
+            );
+    }
+}
+
+impl PgnPlugin {
+    // P saves the game as it is, finished or not
+    fn save_on_request(
+        game: Res<GameState>,
+        settings: Res<PgnSettings>,
+        keyboard: Res<Input<KeyCode>>,
+    ) {
+        if keyboard.just_pressed(KeyCode::P) {
+            save_pgn(&game, &settings);
+        }
+    }
+
+    fn save_at_game_end(game: Res<GameState>, settings: Res<PgnSettings>) {
+        save_pgn(&game, &settings);
+    }
+}
+
+fn save_pgn(game: &GameState, settings: &PgnSettings) {
+    let tags = PgnTags {
+        event: "Casual game".to_string(),
+        site: env!("CARGO_PKG_NAME").to_string(),
+        date: today(),
+        round: "-".to_string(),
+        ..default()
+    };
+    let mut pgn = Pgn::from_game(&game.game, tags);
+    pgn.clocks = game.move_times.clone();
+
+    match fs::write(&settings.path, pgn.to_string()) {
+        Ok(()) => info!("game saved to {}", settings.path.display()),
+        Err(err) => error!(
+            "couldn't save the game to {}: {}",
+            settings.path.display(),
+            err
+        ),
+    }
+}
+
+// the current date as `YYYY.MM.DD`, turning the days since the unix epoch into a civil
+// date of the proleptic gregorian calendar
+fn today() -> String {
+    let days = match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(since_epoch) => since_epoch.as_secs() as i64 / 86_400,
+        Err(_) => return PgnTags::default().date,
+    };
+
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!("{:04}.{:02}.{:02}", year, month, day)
+}
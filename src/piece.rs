@@ -10,21 +10,23 @@ use bevy_mod_picking::{PickableBundle, PickingEvent};
 use iyes_loopless::prelude::*;
 
 use crate::{
+    ai::human_to_move,
     bishop::Bishop,
     board::{Tile, TileState},
+    game::game_in_progress,
     king::King,
     knight::Knight,
-    movement::{get_piece_movements, move_piece, MoveEvent},
+    movement::{get_piece_movements, move_piece, play_move, MoveEvent, PlayMoveEvent},
     pawn::Pawn,
+    promotion::PendingPromotion,
     queen::Queen,
+    replay::Replay,
     rock::Rock,
 };
 
-#[derive(PartialEq, Eq, Clone, Copy)]
-pub enum Team {
-    White,
-    Black,
-}
+pub use bevy_chess::rules::Team;
+use bevy_chess::rules::{Piece, PieceKind};
+
 #[derive(Component)]
 pub struct HighLight;
 
@@ -51,19 +53,49 @@ impl PieceType {
     }
 }
 
+impl From<Piece> for PieceType {
+    fn from(piece: Piece) -> Self {
+        let team = piece.team;
+        match piece.kind {
+            PieceKind::Pawn => Self::Pawn(Pawn { team }),
+            PieceKind::Rock => Self::Rock(Rock { team }),
+            PieceKind::Knight => Self::Knight(Knight { team }),
+            PieceKind::Bishop => Self::Bishop(Bishop { team }),
+            PieceKind::Queen => Self::Queen(Queen { team }),
+            PieceKind::King => Self::King(King { team }),
+        }
+    }
+}
+
 pub struct PieceDeathEvent(pub Entity);
 
 pub struct PiecePlugin;
 
 impl Plugin for PiecePlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(get_piece_movements.run_on_event::<PickingEvent>())
-            .add_system(move_piece.run_on_event::<PickingEvent>())
-            .add_system(reset_neighbors.run_on_event::<MoveEvent>())
-            .add_event::<MoveEvent>()
-            .add_event::<PieceDeathEvent>()
-            .add_system(handle_piece_death.run_on_event::<PieceDeathEvent>())
-            .run();
+        app.add_system(
+            get_piece_movements
+                .run_on_event::<PickingEvent>()
+                .run_if(game_in_progress)
+                .run_if(human_to_move)
+                .run_unless_resource_exists::<PendingPromotion>()
+                .run_unless_resource_exists::<Replay>(),
+        )
+        .add_system(
+            move_piece
+                .run_on_event::<PickingEvent>()
+                .run_if(game_in_progress)
+                .run_if(human_to_move)
+                .run_unless_resource_exists::<PendingPromotion>()
+                .run_unless_resource_exists::<Replay>(),
+        )
+        .add_system(play_move.run_on_event::<PlayMoveEvent>())
+        .add_system(reset_neighbors.run_on_event::<MoveEvent>())
+        .add_event::<MoveEvent>()
+        .add_event::<PlayMoveEvent>()
+        .add_event::<PieceDeathEvent>()
+        .add_system(handle_piece_death.run_on_event::<PieceDeathEvent>())
+        .run();
     }
 }
 
@@ -100,14 +132,25 @@ fn reset_neighbors(
     }
 }
 
+// the tile colors for moves, and for captures that lose material once the opponent takes
+// back
+const MOVE_HIGHLIGHT: &str = "3181C6";
+const RISKY_CAPTURE_HIGHLIGHT: &str = "C65A31";
+
 pub fn highlight_tile(
     commands: &mut Commands,
     grid_size: &TilemapGridSize,
     map_type: &TilemapType,
     tile_pos: &TilePos,
+    risky: bool,
     mesh: &mut Assets<Mesh>,
     material: &mut Assets<ColorMaterial>,
 ) {
+    let color = match risky {
+        true => RISKY_CAPTURE_HIGHLIGHT,
+        false => MOVE_HIGHLIGHT,
+    };
+
     // 2D vector with the x and y of the tile transform
     let vec_t = tile_pos.center_in_world(grid_size, map_type);
 
@@ -116,9 +159,7 @@ pub fn highlight_tile(
             MaterialMesh2dBundle {
                 mesh: Mesh2dHandle(mesh.add(Mesh::from(shape::Quad::new(Vec2::splat(56.0))))),
                 transform: Transform::from_xyz(vec_t.x, vec_t.y, 0.1),
-                material: material.add(ColorMaterial::from(
-                    Color::hex("3181C6").expect("Error here"),
-                )),
+                material: material.add(ColorMaterial::from(Color::hex(color).expect("Error here"))),
                 ..Default::default()
             },
             PickableBundle::default(),
@@ -0,0 +1,93 @@
+use bevy::prelude::*;
+use bevy_chess::rules::{Move, Piece, PieceKind, Team};
+use iyes_loopless::prelude::*;
+
+use crate::{board::TILE_SIZE, movement::PlayMoveEvent, GameAssets};
+
+#[derive(Resource, Default)]
+pub struct PromotionSettings {
+    // promote straight to a queen instead of asking the player
+    pub auto_queen: bool,
+}
+
+// the pawn move waiting for the player to choose what the pawn turns into
+#[derive(Resource)]
+pub struct PendingPromotion(pub Move);
+
+#[derive(Component)]
+pub struct PromotionPicker;
+
+#[derive(Component)]
+pub struct PromotionChoice(PieceKind);
+
+pub struct PromotionPlugin;
+
+impl Plugin for PromotionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PromotionSettings>()
+            .add_system(choose_promotion.run_if_resource_exists::<PendingPromotion>());
+    }
+}
+
+// shows a column with the pieces a pawn of `team` can promote to, next to the board
+pub fn spawn_promotion_picker(commands: &mut Commands, game_assets: &GameAssets, team: Team) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        right: Val::Px(32.0),
+                        top: Val::Px(32.0),
+                        ..default()
+                    },
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(4.0)),
+                    ..default()
+                },
+                background_color: Color::rgb(0.25, 0.25, 0.25).into(),
+                ..default()
+            },
+            PromotionPicker,
+            Name::new("Promotion Picker"),
+        ))
+        .with_children(|picker| {
+            for kind in PieceKind::PROMOTIONS {
+                picker.spawn((
+                    ButtonBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(TILE_SIZE), Val::Px(TILE_SIZE)),
+                            margin: UiRect::all(Val::Px(4.0)),
+                            ..default()
+                        },
+                        image: UiImage(game_assets.texture(Piece::new(kind, team))),
+                        ..default()
+                    },
+                    PromotionChoice(kind),
+                ));
+            }
+        });
+}
+
+// plays the pending promotion with the piece the player clicked on and closes the picker
+fn choose_promotion(
+    mut commands: Commands,
+    pending: Res<PendingPromotion>,
+    choices: Query<(&Interaction, &PromotionChoice), Changed<Interaction>>,
+    picker: Query<Entity, With<PromotionPicker>>,
+    mut play_move: EventWriter<PlayMoveEvent>,
+) {
+    for (interaction, choice) in choices.iter() {
+        if let Interaction::Clicked = interaction {
+            let PendingPromotion(mv) = *pending;
+            play_move.send(PlayMoveEvent(Move::with_promotion(
+                mv.from, mv.to, choice.0,
+            )));
+
+            commands.remove_resource::<PendingPromotion>();
+            for ent in picker.iter() {
+                commands.entity(ent).despawn_recursive();
+            }
+        }
+    }
+}
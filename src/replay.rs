@@ -0,0 +1,67 @@
+use bevy::prelude::*;
+use bevy_chess::rules::{Game, Pgn};
+use iyes_loopless::prelude::*;
+
+use crate::{
+    game::{BoardResetEvent, GameState},
+    movement::PlayMoveEvent,
+};
+
+// a game loaded from a PGN file, while it's there the board only follows the recorded moves
+#[derive(Resource)]
+pub struct Replay(pub Pgn);
+
+impl Replay {
+    // the recorded game after its first `ply` moves
+    pub fn game_at(&self, ply: usize) -> GameState {
+        let mut game = Game::new(self.0.start.clone());
+        for mv in &self.0.moves[..ply] {
+            game.play(*mv);
+        }
+
+        GameState::new(game)
+    }
+}
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(Self::step_forward.run_if_resource_exists::<Replay>())
+            .add_system(Self::step_back.run_if_resource_exists::<Replay>());
+    }
+}
+
+impl ReplayPlugin {
+    // the right arrow plays the next recorded move like any other move
+    fn step_forward(
+        replay: Res<Replay>,
+        game: Res<GameState>,
+        keyboard: Res<Input<KeyCode>>,
+        mut play_move: EventWriter<PlayMoveEvent>,
+    ) {
+        if !keyboard.just_pressed(KeyCode::Right) {
+            return;
+        }
+
+        if let Some(mv) = replay.0.moves.get(game.game.moves().len()) {
+            play_move.send(PlayMoveEvent(*mv));
+        }
+    }
+
+    // the left arrow takes the last move back, by replaying the game up to the move before
+    fn step_back(
+        replay: Res<Replay>,
+        mut game: ResMut<GameState>,
+        keyboard: Res<Input<KeyCode>>,
+        mut board_reset: EventWriter<BoardResetEvent>,
+    ) {
+        let played = game.game.moves().len();
+        if !keyboard.just_pressed(KeyCode::Left) || played == 0 {
+            return;
+        }
+
+        *game = replay.game_at(played - 1);
+        board_reset.send(BoardResetEvent);
+    }
+}
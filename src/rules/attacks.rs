@@ -0,0 +1,39 @@
+use super::{
+    bitboard::{
+        bishop_attacks, king_attacks, knight_attacks, pawn_attacks, queen_attacks, rock_attacks,
+        squares,
+    },
+    Board, PieceKind, Square, Team,
+};
+
+/// Whether any piece of `by` could capture on `square`.
+pub fn is_attacked(board: &Board, square: Square, by: Team) -> bool {
+    let occupied = board.occupied();
+    let pieces = |kind| board.pieces_of(kind, by);
+    let queens = pieces(PieceKind::Queen);
+
+    // attacks are symmetric, a piece standing on `square` would reach the attackers back,
+    // except for pawns which capture towards the other side
+    pawn_attacks(by.opponent(), square) & pieces(PieceKind::Pawn) != 0
+        || knight_attacks(square) & pieces(PieceKind::Knight) != 0
+        || king_attacks(square) & pieces(PieceKind::King) != 0
+        || rock_attacks(square, occupied) & (pieces(PieceKind::Rock) | queens) != 0
+        || bishop_attacks(square, occupied) & (pieces(PieceKind::Bishop) | queens) != 0
+}
+
+/// Every square attacked by `by`, as a bit mask indexed by [`Square::index`].
+pub fn attack_map(board: &Board, by: Team) -> u64 {
+    let occupied = board.occupied();
+
+    squares(board.team(by))
+        .map(|square| match board.get(square).map(|piece| piece.kind) {
+            Some(PieceKind::Pawn) => pawn_attacks(by, square),
+            Some(PieceKind::Knight) => knight_attacks(square),
+            Some(PieceKind::King) => king_attacks(square),
+            Some(PieceKind::Rock) => rock_attacks(square, occupied),
+            Some(PieceKind::Bishop) => bishop_attacks(square, occupied),
+            Some(PieceKind::Queen) => queen_attacks(square, occupied),
+            None => 0,
+        })
+        .fold(0, |map, attacks| map | attacks)
+}
@@ -0,0 +1,152 @@
+//! Sets of squares packed into a `u64`, bit `n` standing for the square with index `n`,
+//! and the attack tables move generation is built on.
+//!
+//! Knights, kings and pawns always attack the same squares from a given square, so those
+//! are looked up. Sliding pieces use hyperbola quintessence: for the pieces on one line
+//! through the slider, `o - 2r` flips every bit up to and including the first blocker above
+//! it, doing the same on the bit-reversed board gives the blocker below it.
+
+use super::{Square, Team};
+
+const KNIGHT_JUMPS: [(i8, i8); 8] = [
+    (1, 2),
+    (-1, 2),
+    (1, -2),
+    (-1, -2),
+    (2, 1),
+    (2, -1),
+    (-2, 1),
+    (-2, -1),
+];
+const ROYAL_STEPS: [(i8, i8); 8] = [
+    (0, 1),
+    (0, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (1, 1),
+    (-1, -1),
+    (1, -1),
+];
+
+static KNIGHT_ATTACKS: [u64; 64] = leaper_table(&KNIGHT_JUMPS);
+static KING_ATTACKS: [u64; 64] = leaper_table(&ROYAL_STEPS);
+static PAWN_ATTACKS: [[u64; 64]; 2] = [
+    leaper_table(&[(-1, 1), (1, 1)]),
+    leaper_table(&[(-1, -1), (1, -1)]),
+];
+// the file, rank, diagonal and anti-diagonal through every square, without the square
+static LINES: [[u64; 4]; 64] = line_table();
+
+const FILE: usize = 0;
+const RANK: usize = 1;
+const DIAGONAL: usize = 2;
+const ANTI_DIAGONAL: usize = 3;
+
+// the squares from `index` walking in one direction until the edge of the board, or just
+// the first one when `slide` is off
+const fn ray(index: usize, files: i8, ranks: i8, slide: bool) -> u64 {
+    let mut bits = 0;
+    let mut file = (index % 8) as i8 + files;
+    let mut rank = (index / 8) as i8 + ranks;
+
+    while file >= 0 && file < 8 && rank >= 0 && rank < 8 {
+        bits |= 1 << (rank as u32 * 8 + file as u32);
+        if !slide {
+            break;
+        }
+        file += files;
+        rank += ranks;
+    }
+
+    bits
+}
+
+const fn leaper_table(steps: &[(i8, i8)]) -> [u64; 64] {
+    let mut table = [0; 64];
+    let mut index = 0;
+
+    while index < 64 {
+        let mut step = 0;
+        while step < steps.len() {
+            table[index] |= ray(index, steps[step].0, steps[step].1, false);
+            step += 1;
+        }
+        index += 1;
+    }
+
+    table
+}
+
+const fn line_table() -> [[u64; 4]; 64] {
+    let mut table = [[0; 4]; 64];
+    let mut index = 0;
+
+    while index < 64 {
+        table[index][FILE] = ray(index, 0, 1, true) | ray(index, 0, -1, true);
+        table[index][RANK] = ray(index, 1, 0, true) | ray(index, -1, 0, true);
+        table[index][DIAGONAL] = ray(index, 1, 1, true) | ray(index, -1, -1, true);
+        table[index][ANTI_DIAGONAL] = ray(index, -1, 1, true) | ray(index, 1, -1, true);
+        index += 1;
+    }
+
+    table
+}
+
+pub(super) fn bit(square: Square) -> u64 {
+    1 << square.index()
+}
+
+/// The squares in `bits`, from a1 to h8.
+pub(crate) fn squares(mut bits: u64) -> impl Iterator<Item = Square> {
+    std::iter::from_fn(move || {
+        if bits == 0 {
+            return None;
+        }
+
+        let index = bits.trailing_zeros() as usize;
+        bits &= bits - 1;
+        Some(Square::from_index(index))
+    })
+}
+
+pub(crate) fn knight_attacks(square: Square) -> u64 {
+    KNIGHT_ATTACKS[square.index()]
+}
+
+pub(crate) fn king_attacks(square: Square) -> u64 {
+    KING_ATTACKS[square.index()]
+}
+
+/// The squares a pawn of `team` standing on `square` captures on.
+pub(crate) fn pawn_attacks(team: Team, square: Square) -> u64 {
+    PAWN_ATTACKS[team.index()][square.index()]
+}
+
+// the squares a slider reaches along one line, up to and including the first piece found
+// each way
+fn line_attacks(square: Square, occupied: u64, line: usize) -> u64 {
+    let mask = LINES[square.index()][line];
+    let slider = bit(square);
+    let blockers = occupied & mask;
+
+    let forward = blockers.wrapping_sub(slider.wrapping_mul(2));
+    let reverse = blockers
+        .reverse_bits()
+        .wrapping_sub(slider.reverse_bits().wrapping_mul(2))
+        .reverse_bits();
+
+    (forward ^ reverse) & mask
+}
+
+pub(crate) fn rock_attacks(square: Square, occupied: u64) -> u64 {
+    line_attacks(square, occupied, FILE) | line_attacks(square, occupied, RANK)
+}
+
+pub(crate) fn bishop_attacks(square: Square, occupied: u64) -> u64 {
+    line_attacks(square, occupied, DIAGONAL) | line_attacks(square, occupied, ANTI_DIAGONAL)
+}
+
+pub(crate) fn queen_attacks(square: Square, occupied: u64) -> u64 {
+    rock_attacks(square, occupied) | bishop_attacks(square, occupied)
+}
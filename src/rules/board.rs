@@ -0,0 +1,105 @@
+use super::{
+    bitboard::{self, bit},
+    Piece, PieceKind, Square, Team,
+};
+
+/// Which piece, if any, stands on each square.
+///
+/// Alongside the piece on every square it keeps a bitboard of the squares each kind of piece
+/// and each team stands on, which is what move generation works with.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Board {
+    squares: [Option<Piece>; 64],
+    kinds: [u64; 6],
+    teams: [u64; 2],
+}
+
+impl Board {
+    pub fn empty() -> Self {
+        Self {
+            squares: [None; 64],
+            kinds: [0; 6],
+            teams: [0; 2],
+        }
+    }
+
+    /// The standard starting layout.
+    pub fn standard() -> Self {
+        let mut board = Self::empty();
+        let back_rank = [
+            PieceKind::Rock,
+            PieceKind::Knight,
+            PieceKind::Bishop,
+            PieceKind::Queen,
+            PieceKind::King,
+            PieceKind::Bishop,
+            PieceKind::Knight,
+            PieceKind::Rock,
+        ];
+
+        for (file, kind) in back_rank.into_iter().enumerate() {
+            let file = file as u8;
+            board.set(Square::new(file, 0), Some(Piece::new(kind, Team::White)));
+            board.set(
+                Square::new(file, 1),
+                Some(Piece::new(PieceKind::Pawn, Team::White)),
+            );
+            board.set(
+                Square::new(file, 6),
+                Some(Piece::new(PieceKind::Pawn, Team::Black)),
+            );
+            board.set(Square::new(file, 7), Some(Piece::new(kind, Team::Black)));
+        }
+
+        board
+    }
+
+    pub fn get(&self, square: Square) -> Option<Piece> {
+        self.squares[square.index()]
+    }
+
+    pub fn set(&mut self, square: Square, piece: Option<Piece>) {
+        if let Some(old) = self.squares[square.index()] {
+            self.kinds[old.kind.index()] &= !bit(square);
+            self.teams[old.team.index()] &= !bit(square);
+        }
+        if let Some(new) = piece {
+            self.kinds[new.kind.index()] |= bit(square);
+            self.teams[new.team.index()] |= bit(square);
+        }
+
+        self.squares[square.index()] = piece;
+    }
+
+    /// Where the king of `team` stands, if it's on the board.
+    pub fn king_square(&self, team: Team) -> Option<Square> {
+        bitboard::squares(self.pieces_of(PieceKind::King, team)).next()
+    }
+
+    /// Every occupied square together with the piece on it.
+    pub fn pieces(&self) -> impl Iterator<Item = (Square, Piece)> + '_ {
+        bitboard::squares(self.occupied())
+            .filter_map(|square| self.get(square).map(|piece| (square, piece)))
+    }
+
+    /// The squares with a piece on them, as a bitboard indexed by [`Square::index`].
+    pub fn occupied(&self) -> u64 {
+        self.teams[0] | self.teams[1]
+    }
+
+    /// The squares the pieces of `team` stand on.
+    pub fn team(&self, team: Team) -> u64 {
+        self.teams[team.index()]
+    }
+
+    /// The squares the pieces of `kind` of `team` stand on.
+    pub fn pieces_of(&self, kind: PieceKind, team: Team) -> u64 {
+        self.kinds[kind.index()] & self.teams[team.index()]
+    }
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
@@ -0,0 +1,130 @@
+use super::{Move, PieceKind, Position, Square, Team};
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum CastleSide {
+    King,
+    Queen,
+}
+
+impl CastleSide {
+    // file the rook starts on, and the files the king and the rook end up on
+    fn files(self) -> (u8, u8, u8) {
+        match self {
+            Self::King => (7, 6, 5),
+            Self::Queen => (0, 2, 3),
+        }
+    }
+
+    /// The squares between the king and the rook, which have to be empty to castle.
+    pub fn path(self, team: Team) -> Vec<Square> {
+        let rank = team.back_rank();
+        match self {
+            Self::King => vec![Square::new(5, rank), Square::new(6, rank)],
+            Self::Queen => vec![
+                Square::new(1, rank),
+                Square::new(2, rank),
+                Square::new(3, rank),
+            ],
+        }
+    }
+
+    /// The rook's move that goes along with the king's when castling to this side.
+    pub fn rook_move(self, team: Team) -> Move {
+        let rank = team.back_rank();
+        let (rook_file, _, rook_to) = self.files();
+        Move::new(Square::new(rook_file, rank), Square::new(rook_to, rank))
+    }
+
+    pub fn king_move(self, team: Team) -> Move {
+        let rank = team.back_rank();
+        let (_, king_to, _) = self.files();
+        Move::new(Square::new(4, rank), Square::new(king_to, rank))
+    }
+}
+
+/// Which castles are still allowed, they are lost for good once the king or the rook moves.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+pub struct CastlingRights {
+    pub white_king_side: bool,
+    pub white_queen_side: bool,
+    pub black_king_side: bool,
+    pub black_queen_side: bool,
+}
+
+impl CastlingRights {
+    pub fn all() -> Self {
+        Self {
+            white_king_side: true,
+            white_queen_side: true,
+            black_king_side: true,
+            black_queen_side: true,
+        }
+    }
+
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    pub fn has(&self, team: Team, side: CastleSide) -> bool {
+        *self.right(team, side)
+    }
+
+    pub fn add(&mut self, team: Team, side: CastleSide) {
+        *self.right_mut(team, side) = true;
+    }
+
+    pub fn remove(&mut self, team: Team, side: CastleSide) {
+        *self.right_mut(team, side) = false;
+    }
+
+    fn right(&self, team: Team, side: CastleSide) -> &bool {
+        match (team, side) {
+            (Team::White, CastleSide::King) => &self.white_king_side,
+            (Team::White, CastleSide::Queen) => &self.white_queen_side,
+            (Team::Black, CastleSide::King) => &self.black_king_side,
+            (Team::Black, CastleSide::Queen) => &self.black_queen_side,
+        }
+    }
+
+    fn right_mut(&mut self, team: Team, side: CastleSide) -> &mut bool {
+        match (team, side) {
+            (Team::White, CastleSide::King) => &mut self.white_king_side,
+            (Team::White, CastleSide::Queen) => &mut self.white_queen_side,
+            (Team::Black, CastleSide::King) => &mut self.black_king_side,
+            (Team::Black, CastleSide::Queen) => &mut self.black_queen_side,
+        }
+    }
+
+    // a move touching the king's or a rook's starting square ends the matching rights, this
+    // covers the king or rook moving away as well as the rook being captured at home
+    pub(super) fn update(&mut self, mv: Move) {
+        for team in [Team::White, Team::Black] {
+            for side in [CastleSide::King, CastleSide::Queen] {
+                let home = [side.king_move(team).from, side.rook_move(team).from];
+                if home.contains(&mv.from) || home.contains(&mv.to) {
+                    self.remove(team, side);
+                }
+            }
+        }
+    }
+}
+
+impl Position {
+    /// The side `mv` castles to, if it's a castling move of the king.
+    pub fn castle_side(&self, mv: Move) -> Option<CastleSide> {
+        let piece = self.board().get(mv.from)?;
+        if piece.kind != PieceKind::King {
+            return None;
+        }
+
+        [CastleSide::King, CastleSide::Queen]
+            .into_iter()
+            .find(|side| side.king_move(piece.team) == mv)
+    }
+
+    /// The rook's part of `mv` when it castles, so both pieces can be relocated.
+    pub fn castling_rook(&self, mv: Move) -> Option<Move> {
+        let team = self.board().get(mv.from)?.team;
+        self.castle_side(mv).map(|side| side.rook_move(team))
+    }
+}
@@ -0,0 +1,261 @@
+use std::{fmt, str::FromStr};
+
+use super::{Board, CastleSide, CastlingRights, Piece, PieceKind, Position, Square, Team};
+
+/// The standard starting position.
+pub const STARTING_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// Why a FEN string couldn't be read, naming the field that is wrong.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FenError {
+    /// A FEN needs at least the placement, side, castling and en passant fields.
+    MissingField(&'static str),
+    TooManyFields,
+    Placement(String),
+    SideToMove(String),
+    Castling(String),
+    EnPassant(String),
+    Clock(String),
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MissingField(field) => write!(f, "missing the {} field", field),
+            Self::TooManyFields => write!(f, "too many fields"),
+            Self::Placement(reason) => write!(f, "invalid piece placement: {}", reason),
+            Self::SideToMove(side) => write!(f, "invalid side to move: {:?}", side),
+            Self::Castling(castling) => write!(f, "invalid castling rights: {:?}", castling),
+            Self::EnPassant(square) => write!(f, "invalid en passant square: {:?}", square),
+            Self::Clock(clock) => write!(f, "invalid move counter: {:?}", clock),
+        }
+    }
+}
+
+impl std::error::Error for FenError {}
+
+fn piece_from_char(c: char) -> Option<Piece> {
+    let kind = match c.to_ascii_uppercase() {
+        'P' => PieceKind::Pawn,
+        'R' => PieceKind::Rock,
+        'N' => PieceKind::Knight,
+        'B' => PieceKind::Bishop,
+        'Q' => PieceKind::Queen,
+        'K' => PieceKind::King,
+        _ => return None,
+    };
+    let team = if c.is_ascii_uppercase() {
+        Team::White
+    } else {
+        Team::Black
+    };
+
+    Some(Piece::new(kind, team))
+}
+
+// white pieces are written in uppercase and black ones in lowercase
+fn piece_to_char(piece: Piece) -> char {
+    match piece.team {
+        Team::White => piece.kind.symbol(),
+        Team::Black => piece.kind.symbol().to_ascii_lowercase(),
+    }
+}
+
+// ranks go from the 8th down to the 1st, each one from the a file to the h file
+fn parse_placement(placement: &str) -> Result<Board, FenError> {
+    let mut board = Board::empty();
+    let ranks: Vec<&str> = placement.split('/').collect();
+    if ranks.len() != 8 {
+        return Err(FenError::Placement(format!(
+            "expected 8 ranks, found {}",
+            ranks.len()
+        )));
+    }
+
+    for (row, pieces) in ranks.into_iter().enumerate() {
+        let rank = 7 - row as u8;
+        let mut file = 0;
+
+        for c in pieces.chars() {
+            if let Some(empty) = c.to_digit(10).filter(|n| (1..=8).contains(n)) {
+                file += empty as u8;
+            } else if let Some(piece) = piece_from_char(c) {
+                if file < 8 {
+                    board.set(Square::new(file, rank), Some(piece));
+                }
+                file += 1;
+            } else {
+                return Err(FenError::Placement(format!("unknown piece {:?}", c)));
+            }
+
+            if file > 8 {
+                break;
+            }
+        }
+
+        if file != 8 {
+            return Err(FenError::Placement(format!(
+                "rank {} doesn't have 8 squares",
+                rank + 1
+            )));
+        }
+    }
+
+    // the rules need both kings on the board to tell checks apart
+    for team in [Team::White, Team::Black] {
+        let kings = board
+            .pieces()
+            .filter(|(_, piece)| *piece == Piece::new(PieceKind::King, team))
+            .count();
+        if kings != 1 {
+            return Err(FenError::Placement(format!(
+                "{:?} has {} kings instead of one",
+                team, kings
+            )));
+        }
+    }
+
+    Ok(board)
+}
+
+fn parse_castling(castling: &str) -> Result<CastlingRights, FenError> {
+    let mut rights = CastlingRights::none();
+    if castling == "-" {
+        return Ok(rights);
+    }
+
+    for c in castling.chars() {
+        let (team, side) = match c {
+            'K' => (Team::White, CastleSide::King),
+            'Q' => (Team::White, CastleSide::Queen),
+            'k' => (Team::Black, CastleSide::King),
+            'q' => (Team::Black, CastleSide::Queen),
+            _ => return Err(FenError::Castling(castling.to_string())),
+        };
+        if rights.has(team, side) {
+            return Err(FenError::Castling(castling.to_string()));
+        }
+        rights.add(team, side);
+    }
+
+    Ok(rights)
+}
+
+impl Position {
+    /// Reads a position from Forsyth-Edwards Notation. The two move counters can be left
+    /// out, in which case they are taken as `0 1`.
+    pub fn from_fen(fen: &str) -> Result<Self, FenError> {
+        let mut fields = fen.split_whitespace();
+        let mut field = |name| fields.next().ok_or(FenError::MissingField(name));
+
+        let board = parse_placement(field("piece placement")?)?;
+        let side_to_move = match field("side to move")? {
+            "w" => Team::White,
+            "b" => Team::Black,
+            side => return Err(FenError::SideToMove(side.to_string())),
+        };
+        let castling = parse_castling(field("castling")?)?;
+        // the skipped square is right behind a pawn of the side that just moved
+        let en_passant_rank = match side_to_move {
+            Team::White => 5,
+            Team::Black => 2,
+        };
+        let en_passant = match field("en passant")? {
+            "-" => None,
+            square => match square.parse::<Square>() {
+                Ok(sq) if sq.rank() == en_passant_rank => Some(sq),
+                _ => return Err(FenError::EnPassant(square.to_string())),
+            },
+        };
+        let mut clock = |default: u32| match fields.next() {
+            Some(clock) => clock
+                .parse::<u32>()
+                .map_err(|_| FenError::Clock(clock.to_string())),
+            None => Ok(default),
+        };
+        let halfmove_clock = clock(0)?;
+        let fullmove_number = clock(1)?.max(1);
+
+        if fields.next().is_some() {
+            return Err(FenError::TooManyFields);
+        }
+
+        let mut position = Self::new(board, side_to_move);
+        position.set_castling_rights(castling);
+        position.set_en_passant(en_passant);
+        position.set_halfmove_clock(halfmove_clock);
+        position.set_fullmove_number(fullmove_number);
+
+        Ok(position)
+    }
+
+    /// Writes the position in Forsyth-Edwards Notation.
+    pub fn to_fen(&self) -> String {
+        let mut fen = String::new();
+
+        for rank in (0..8).rev() {
+            let mut empty = 0;
+            for file in 0..8 {
+                match self.board().get(Square::new(file, rank)) {
+                    Some(piece) => {
+                        if empty > 0 {
+                            fen.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        fen.push(piece_to_char(piece));
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                fen.push_str(&empty.to_string());
+            }
+            if rank > 0 {
+                fen.push('/');
+            }
+        }
+
+        let side = match self.side_to_move() {
+            Team::White => 'w',
+            Team::Black => 'b',
+        };
+
+        let rights = self.castling_rights();
+        let mut castling: String = [
+            (Team::White, CastleSide::King, 'K'),
+            (Team::White, CastleSide::Queen, 'Q'),
+            (Team::Black, CastleSide::King, 'k'),
+            (Team::Black, CastleSide::Queen, 'q'),
+        ]
+        .into_iter()
+        .filter(|(team, side, _)| rights.has(*team, *side))
+        .map(|(_, _, c)| c)
+        .collect();
+        if castling.is_empty() {
+            castling.push('-');
+        }
+
+        let en_passant = match self.en_passant() {
+            Some(square) => square.to_string(),
+            None => "-".to_string(),
+        };
+
+        format!(
+            "{} {} {} {} {} {}",
+            fen,
+            side,
+            castling,
+            en_passant,
+            self.halfmove_clock(),
+            self.fullmove_number()
+        )
+    }
+}
+
+impl FromStr for Position {
+    type Err = FenError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_fen(s)
+    }
+}
@@ -0,0 +1,87 @@
+use super::{EndReason, GameResult, Move, Outcome, Piece, Position, Team};
+
+/// A position together with the moves and positions that led to it, which the repetition
+/// rule and the game record need.
+#[derive(Debug, Clone)]
+pub struct Game {
+    start: Position,
+    position: Position,
+    moves: Vec<Move>,
+    // the Zobrist keys of every position so far, for the repetition rule
+    history: Vec<u64>,
+}
+
+impl Game {
+    pub fn new(position: Position) -> Self {
+        Self {
+            start: position.clone(),
+            history: vec![position.zobrist()],
+            moves: Vec::new(),
+            position,
+        }
+    }
+
+    /// The position the game started from.
+    pub fn start(&self) -> &Position {
+        &self.start
+    }
+
+    pub fn position(&self) -> &Position {
+        &self.position
+    }
+
+    /// Every move played so far, in order.
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// The moves played so far in Standard Algebraic Notation.
+    pub fn san_moves(&self) -> Vec<String> {
+        let mut position = self.start.clone();
+
+        self.moves
+            .iter()
+            .map(|mv| {
+                let san = position.san(*mv);
+                position.apply(*mv);
+                san
+            })
+            .collect()
+    }
+
+    pub fn side_to_move(&self) -> Team {
+        self.position.side_to_move()
+    }
+
+    /// Plays `mv` on the current position and remembers the position it leads to.
+    pub fn play(&mut self, mv: Move) -> Option<Piece> {
+        let captured = self.position.apply(mv);
+        self.moves.push(mv);
+        self.history.push(self.position.zobrist());
+
+        captured
+    }
+
+    /// How many times the current position has been on the board, counting now.
+    pub fn repetitions(&self) -> usize {
+        let current = self.position.zobrist();
+        self.history.iter().filter(|key| **key == current).count()
+    }
+
+    /// The way the game has ended, if it has, covering every rule [`Position::outcome`]
+    /// does plus threefold repetition.
+    pub fn outcome(&self) -> Option<Outcome> {
+        self.position.outcome().or_else(|| {
+            (self.repetitions() >= 3).then_some(Outcome {
+                result: GameResult::Draw,
+                reason: EndReason::ThreefoldRepetition,
+            })
+        })
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new(Position::default())
+    }
+}
@@ -0,0 +1,45 @@
+//! Pure-Rust model of a chess game: board, position and move generation.
+//!
+//! Nothing in here knows about Bevy, so the rules can be unit-tested, reused by
+//! tools and run without a window. The ECS systems translate tiles to [`Square`]s
+//! and consume [`Position::legal_moves`] / [`Position::apply`].
+//!
+//! Generated moves are fully legal: a move that would leave the mover's own king in
+//! check is never produced.
+
+mod attacks;
+mod bitboard;
+mod board;
+mod castling;
+mod fen;
+mod game;
+mod movegen;
+mod moves;
+mod outcome;
+mod perft;
+mod pgn;
+mod piece;
+mod position;
+mod san;
+mod square;
+mod zobrist;
+
+pub use attacks::{attack_map, is_attacked};
+// the engine's evaluation looks at the same attack tables
+pub(crate) use bitboard::{
+    bishop_attacks, king_attacks, knight_attacks, pawn_attacks, queen_attacks, rock_attacks,
+    squares,
+};
+pub use board::Board;
+pub use castling::{CastleSide, CastlingRights};
+pub use fen::{FenError, STARTING_FEN};
+pub use game::Game;
+pub use movegen::legal_moves;
+pub use moves::{Move, ParseMoveError};
+pub use outcome::{EndReason, GameResult, Outcome};
+pub use perft::{divide, perft};
+pub use pgn::{Pgn, PgnError, PgnTags};
+pub use piece::{Piece, PieceKind, Team};
+pub use position::Position;
+pub use san::SanError;
+pub use square::Square;
@@ -0,0 +1,147 @@
+use super::{
+    attacks,
+    bitboard::{
+        bishop_attacks, bit, king_attacks, knight_attacks, pawn_attacks, queen_attacks,
+        rock_attacks, squares,
+    },
+    CastleSide, Move, Piece, PieceKind, Position, Square,
+};
+
+/// Every move the side to move can play in `position` without leaving its king in check.
+pub fn legal_moves(position: &Position) -> Vec<Move> {
+    let mut moves = Vec::new();
+
+    for (square, piece) in position.board().pieces() {
+        if piece.team == position.side_to_move() {
+            piece_moves(position, square, piece, &mut moves);
+        }
+    }
+
+    moves.retain(|mv| keeps_king_safe(position, *mv));
+    moves
+}
+
+pub(super) fn moves_from(position: &Position, from: Square) -> Vec<Move> {
+    let mut moves = Vec::new();
+
+    if let Some(piece) = position.board().get(from) {
+        piece_moves(position, from, piece, &mut moves);
+    }
+
+    moves.retain(|mv| keeps_king_safe(position, *mv));
+    moves
+}
+
+// plays the move on a copy of the board and checks the mover's king isn't attacked
+// afterwards, which rules out pinned pieces and walking into or ignoring a check
+fn keeps_king_safe(position: &Position, mv: Move) -> bool {
+    let team = match position.board().get(mv.from) {
+        Some(piece) => piece.team,
+        None => return false,
+    };
+    let mut after = position.clone();
+    after.apply(mv);
+
+    !after.is_in_check(team)
+}
+
+fn piece_moves(position: &Position, from: Square, piece: Piece, moves: &mut Vec<Move>) {
+    let occupied = position.board().occupied();
+    let targets = match piece.kind {
+        PieceKind::Pawn => return pawn_moves(position, from, piece, moves),
+        PieceKind::Knight => knight_attacks(from),
+        PieceKind::King => {
+            castle_moves(position, from, piece, moves);
+            king_attacks(from)
+        }
+        PieceKind::Rock => rock_attacks(from, occupied),
+        PieceKind::Bishop => bishop_attacks(from, occupied),
+        PieceKind::Queen => queen_attacks(from, occupied),
+    };
+
+    // a square can be moved to if it's empty or holds a piece of the opposite team
+    let targets = targets & !position.board().team(piece.team);
+    moves.extend(squares(targets).map(|to| Move::new(from, to)));
+}
+
+fn pawn_moves(position: &Position, from: Square, piece: Piece, moves: &mut Vec<Move>) {
+    let board = position.board();
+    let forward = piece.team.forward();
+    let start_rank = if forward > 0 { 1 } else { 6 };
+
+    // pushes only go through empty tiles, and the double step needs both of them free
+    if let Some(one) = from
+        .offset(0, forward)
+        .filter(|sq| board.get(*sq).is_none())
+    {
+        push_pawn_move(from, one, piece, moves);
+
+        if from.rank() == start_rank {
+            if let Some(two) = one.offset(0, forward).filter(|sq| board.get(*sq).is_none()) {
+                moves.push(Move::new(from, two));
+            }
+        }
+    }
+
+    // diagonal captures, including the pawn that just passed by with a double step
+    let mut victims = board.team(piece.team.opponent());
+    if let Some(en_passant) = position.en_passant() {
+        if piece.team == position.side_to_move() {
+            victims |= bit(en_passant);
+        }
+    }
+    for to in squares(pawn_attacks(piece.team, from) & victims) {
+        push_pawn_move(from, to, piece, moves);
+    }
+}
+
+// a pawn reaching the last rank has to promote, so there's one move per piece it can become
+fn push_pawn_move(from: Square, to: Square, pawn: Piece, moves: &mut Vec<Move>) {
+    if to.rank() == pawn.team.last_rank() {
+        moves.extend(
+            PieceKind::PROMOTIONS
+                .iter()
+                .map(|kind| Move::with_promotion(from, to, *kind)),
+        );
+    } else {
+        moves.push(Move::new(from, to));
+    }
+}
+
+// the king goes two tiles towards a rook that hasn't moved when nothing stands between
+// them, as long as it isn't in check and doesn't cross an attacked tile. Landing in check
+// is ruled out later like for any other move
+fn castle_moves(position: &Position, from: Square, piece: Piece, moves: &mut Vec<Move>) {
+    let board = position.board();
+    let sides: Vec<CastleSide> = [CastleSide::King, CastleSide::Queen]
+        .into_iter()
+        .filter(|side| {
+            position.castling_rights().has(piece.team, *side)
+                && side.king_move(piece.team).from == from
+                && board.get(side.rook_move(piece.team).from)
+                    == Some(Piece::new(PieceKind::Rock, piece.team))
+                && side
+                    .path(piece.team)
+                    .iter()
+                    .all(|sq| board.get(*sq).is_none())
+        })
+        .collect();
+    if sides.is_empty() {
+        return;
+    }
+
+    // one map of the enemy's attacks covers the king's tile and the ones it crosses
+    let attacked = attacks::attack_map(board, piece.team.opponent());
+    for side in sides {
+        let king_move = side.king_move(piece.team);
+        // the tile the king passes over on its way
+        let crossed = Square::new(
+            (king_move.from.file() + king_move.to.file()) / 2,
+            from.rank(),
+        );
+
+        if attacked & (bit(from) | bit(crossed)) == 0 {
+            moves.push(king_move);
+        }
+    }
+}
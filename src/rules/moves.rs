@@ -0,0 +1,105 @@
+use std::{fmt, str::FromStr};
+
+use super::{PieceKind, Position, Square};
+
+/// A move of the piece standing on `from` to `to`, pawns reaching the last rank also say
+/// which piece they turn into.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Move {
+    pub from: Square,
+    pub to: Square,
+    pub promotion: Option<PieceKind>,
+}
+
+impl Move {
+    pub fn new(from: Square, to: Square) -> Self {
+        Self {
+            from,
+            to,
+            promotion: None,
+        }
+    }
+
+    pub fn with_promotion(from: Square, to: Square, promotion: PieceKind) -> Self {
+        Self {
+            from,
+            to,
+            promotion: Some(promotion),
+        }
+    }
+}
+
+/// Long algebraic notation as used by UCI, `e2e4` or `e7e8q`.
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.from, self.to)?;
+
+        if let Some(kind) = self.promotion {
+            write!(f, "{}", kind.symbol().to_ascii_lowercase())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Why a move in long algebraic notation couldn't be read or played.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseMoveError {
+    /// The text isn't two squares followed by an optional promotion letter.
+    Invalid(String),
+    /// The move isn't legal in the position it was read for.
+    Illegal(String),
+}
+
+impl fmt::Display for ParseMoveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Invalid(uci) => write!(f, "{:?} isn't a move", uci),
+            Self::Illegal(uci) => write!(f, "{:?} isn't legal in this position", uci),
+        }
+    }
+}
+
+impl std::error::Error for ParseMoveError {}
+
+impl FromStr for Move {
+    type Err = ParseMoveError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseMoveError::Invalid(s.to_string());
+        if !s.is_ascii() || !(4..=5).contains(&s.len()) {
+            return Err(invalid());
+        }
+
+        let from = s[0..2].parse().map_err(|_| invalid())?;
+        let to = s[2..4].parse().map_err(|_| invalid())?;
+        let promotion = match s[4..].chars().next() {
+            Some(symbol) => Some(
+                PieceKind::PROMOTIONS
+                    .into_iter()
+                    .find(|kind| kind.symbol() == symbol.to_ascii_uppercase())
+                    .ok_or_else(invalid)?,
+            ),
+            None => None,
+        };
+
+        Ok(Self {
+            from,
+            to,
+            promotion,
+        })
+    }
+}
+
+impl Position {
+    /// Reads a move in long algebraic notation and checks it's legal here. A pawn reaching
+    /// the last rank has to say what it promotes to.
+    pub fn parse_uci(&self, uci: &str) -> Result<Move, ParseMoveError> {
+        let mv: Move = uci.trim().parse()?;
+
+        match self.legal_moves().contains(&mv) {
+            true => Ok(mv),
+            false => Err(ParseMoveError::Illegal(uci.to_string())),
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use std::fmt;
+
+use super::{PieceKind, Position, Square, Team};
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+}
+
+impl GameResult {
+    pub fn win_for(team: Team) -> Self {
+        match team {
+            Team::White => Self::WhiteWins,
+            Team::Black => Self::BlackWins,
+        }
+    }
+}
+
+impl fmt::Display for GameResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::WhiteWins => "1-0",
+            Self::BlackWins => "0-1",
+            Self::Draw => "1/2-1/2",
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum EndReason {
+    Checkmate,
+    Stalemate,
+    FiftyMoveRule,
+    ThreefoldRepetition,
+    InsufficientMaterial,
+}
+
+impl fmt::Display for EndReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Checkmate => "checkmate",
+            Self::Stalemate => "stalemate",
+            Self::FiftyMoveRule => "the fifty-move rule",
+            Self::ThreefoldRepetition => "threefold repetition",
+            Self::InsufficientMaterial => "insufficient material",
+        })
+    }
+}
+
+/// How and why a game finished.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Outcome {
+    pub result: GameResult,
+    pub reason: EndReason,
+}
+
+impl Outcome {
+    fn draw(reason: EndReason) -> Self {
+        Self {
+            result: GameResult::Draw,
+            reason,
+        }
+    }
+}
+
+impl Position {
+    /// The way the game has ended in this position, if it has. Repetitions need the moves
+    /// that led here, see [`Game::outcome`](super::Game::outcome) for those.
+    pub fn outcome(&self) -> Option<Outcome> {
+        if self.legal_moves().is_empty() {
+            return Some(if self.in_check() {
+                Outcome {
+                    result: GameResult::win_for(self.side_to_move().opponent()),
+                    reason: EndReason::Checkmate,
+                }
+            } else {
+                Outcome::draw(EndReason::Stalemate)
+            });
+        }
+
+        if self.halfmove_clock() >= 100 {
+            Some(Outcome::draw(EndReason::FiftyMoveRule))
+        } else if self.has_insufficient_material() {
+            Some(Outcome::draw(EndReason::InsufficientMaterial))
+        } else {
+            None
+        }
+    }
+
+    /// Whether neither side has enough pieces left to ever deliver mate: lone kings, a single
+    /// knight or bishop against a lone king, or any number of bishops all on one colour.
+    pub fn has_insufficient_material(&self) -> bool {
+        let others: Vec<(Square, PieceKind)> = self
+            .board()
+            .pieces()
+            .filter(|(_, piece)| piece.kind != PieceKind::King)
+            .map(|(square, piece)| (square, piece.kind))
+            .collect();
+
+        match others.as_slice() {
+            [] => true,
+            [(_, PieceKind::Knight | PieceKind::Bishop)] => true,
+            bishops => {
+                let colour = |square: &Square| (square.file() + square.rank()) % 2;
+                bishops.iter().all(|(_, kind)| *kind == PieceKind::Bishop)
+                    && bishops
+                        .iter()
+                        .all(|(square, _)| colour(square) == colour(&bishops[0].0))
+            }
+        }
+    }
+}
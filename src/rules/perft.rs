@@ -0,0 +1,38 @@
+use super::{Move, Position};
+
+/// Counts the leaf nodes of the move tree `depth` plies deep, to compare the move generator
+/// against known counts for reference positions.
+pub fn perft(position: &Position, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    let moves = position.legal_moves();
+    // the moves themselves are the leaves, no need to play them
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+
+    moves
+        .into_iter()
+        .map(|mv| {
+            let mut next = position.clone();
+            next.apply(mv);
+            perft(&next, depth - 1)
+        })
+        .sum()
+}
+
+/// [`perft`] split by the first move, which points at the move a wrong count comes from
+/// when compared with another generator's.
+pub fn divide(position: &Position, depth: u32) -> Vec<(Move, u64)> {
+    position
+        .legal_moves()
+        .into_iter()
+        .map(|mv| {
+            let mut next = position.clone();
+            next.apply(mv);
+            (mv, perft(&next, depth.saturating_sub(1)))
+        })
+        .collect()
+}
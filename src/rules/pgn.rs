@@ -0,0 +1,353 @@
+use std::{fmt, str::FromStr, time::Duration};
+
+use super::{FenError, Game, GameResult, Move, Position, SanError, Team};
+
+// export format keeps movetext lines under 80 characters
+const LINE_WIDTH: usize = 79;
+
+/// The Seven Tag Roster every PGN game starts with, the result tag comes from the game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgnTags {
+    pub event: String,
+    pub site: String,
+    /// `YYYY.MM.DD`, with `?`s for the unknown parts.
+    pub date: String,
+    pub round: String,
+    pub white: String,
+    pub black: String,
+}
+
+impl Default for PgnTags {
+    fn default() -> Self {
+        Self {
+            event: "?".to_string(),
+            site: "?".to_string(),
+            date: "????.??.??".to_string(),
+            round: "?".to_string(),
+            white: "?".to_string(),
+            black: "?".to_string(),
+        }
+    }
+}
+
+/// A game as written to a PGN file: its tags, the moves from the starting position and the
+/// result, `None` while it's still being played.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pgn {
+    pub tags: PgnTags,
+    pub start: Position,
+    pub moves: Vec<Move>,
+    /// Time spent on each move, written as `[%emt]` clock comments. Left empty when the
+    /// moves weren't timed.
+    pub clocks: Vec<Duration>,
+    pub result: Option<GameResult>,
+}
+
+impl Pgn {
+    /// The record of `game` so far, its result is set once the game is over.
+    pub fn from_game(game: &Game, tags: PgnTags) -> Self {
+        Self {
+            tags,
+            start: game.start().clone(),
+            moves: game.moves().to_vec(),
+            clocks: Vec::new(),
+            result: game.outcome().map(|outcome| outcome.result),
+        }
+    }
+
+    fn result_token(&self) -> String {
+        match self.result {
+            Some(result) => result.to_string(),
+            None => "*".to_string(),
+        }
+    }
+
+    // the numbered moves, each one followed by its clock comment if there is one
+    fn movetext_tokens(&self) -> Vec<String> {
+        let mut tokens = Vec::new();
+        let mut position = self.start.clone();
+
+        for (i, mv) in self.moves.iter().enumerate() {
+            match position.side_to_move() {
+                Team::White => tokens.push(format!("{}.", position.fullmove_number())),
+                // a game starting with Black to move, or resuming after a comment
+                Team::Black if i == 0 || self.clocks.get(i - 1).is_some() => {
+                    tokens.push(format!("{}...", position.fullmove_number()))
+                }
+                Team::Black => {}
+            }
+
+            tokens.push(position.san(*mv));
+            position.apply(*mv);
+
+            if let Some(clock) = self.clocks.get(i) {
+                tokens.push(format!("{{[%emt {}]}}", format_clock(*clock)));
+            }
+        }
+
+        tokens.push(self.result_token());
+        tokens
+    }
+}
+
+// `h:mm:ss`, as clock commands expect
+fn format_clock(clock: Duration) -> String {
+    let seconds = clock.as_secs();
+    format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+impl fmt::Display for Pgn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let tags = [
+            ("Event", &self.tags.event),
+            ("Site", &self.tags.site),
+            ("Date", &self.tags.date),
+            ("Round", &self.tags.round),
+            ("White", &self.tags.white),
+            ("Black", &self.tags.black),
+            ("Result", &self.result_token()),
+        ];
+        for (name, value) in tags {
+            writeln!(f, "[{} \"{}\"]", name, escape(value))?;
+        }
+
+        // games that don't start from the standard layout carry their first position
+        if self.start != Position::default() {
+            writeln!(f, "[SetUp \"1\"]")?;
+            writeln!(f, "[FEN \"{}\"]", self.start.to_fen())?;
+        }
+        writeln!(f)?;
+
+        let mut line = String::new();
+        for token in self.movetext_tokens() {
+            if !line.is_empty() && line.len() + 1 + token.len() > LINE_WIDTH {
+                writeln!(f, "{}", line)?;
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&token);
+        }
+        writeln!(f, "{}", line)
+    }
+}
+
+/// Why a PGN game couldn't be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PgnError {
+    /// A tag pair that isn't `[Name "value"]`.
+    Tag(String),
+    Fen(FenError),
+    /// The move at `ply`, counting from 1, isn't a legal move of the position it's played in.
+    Move {
+        ply: usize,
+        error: SanError,
+    },
+}
+
+impl fmt::Display for PgnError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Tag(tag) => write!(f, "invalid tag pair: {}", tag),
+            Self::Fen(err) => write!(f, "invalid FEN tag: {}", err),
+            Self::Move { ply, error } => write!(f, "ply {}: {}", ply, error),
+        }
+    }
+}
+
+impl std::error::Error for PgnError {}
+
+// `[Name "value"]`, with backslashes escaping quotes and themselves in the value
+fn parse_tag(line: &str) -> Result<(&str, String), PgnError> {
+    let invalid = || PgnError::Tag(line.to_string());
+    let inner = line
+        .strip_prefix('[')
+        .and_then(|tag| tag.strip_suffix(']'))
+        .ok_or_else(invalid)?;
+    let (name, value) = inner
+        .trim()
+        .split_once(char::is_whitespace)
+        .ok_or_else(invalid)?;
+    let value = value
+        .trim()
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .ok_or_else(invalid)?;
+
+    let mut unescaped = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unescaped.extend(chars.next()),
+            c => unescaped.push(c),
+        }
+    }
+
+    Ok((name, unescaped))
+}
+
+fn parse_result(token: &str) -> Option<Option<GameResult>> {
+    match token {
+        "1-0" => Some(Some(GameResult::WhiteWins)),
+        "0-1" => Some(Some(GameResult::BlackWins)),
+        "1/2-1/2" => Some(Some(GameResult::Draw)),
+        "*" => Some(None),
+        _ => None,
+    }
+}
+
+impl Pgn {
+    /// Reads the first game of a PGN file, checking every move against the position it's
+    /// played in. Comments, annotation glyphs and variations are skipped.
+    pub fn parse(pgn: &str) -> Result<Self, PgnError> {
+        let mut tags = PgnTags::default();
+        let mut start = Position::default();
+        let mut result = None;
+
+        // the tag pairs come first, the movetext starts with the first line that isn't one
+        let mut lines = pgn.lines().peekable();
+        while let Some(line) = lines.next_if(|line| {
+            let line = line.trim();
+            line.is_empty() || line.starts_with('[')
+        }) {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (name, value) = parse_tag(line)?;
+            match name {
+                "Event" => tags.event = value,
+                "Site" => tags.site = value,
+                "Date" => tags.date = value,
+                "Round" => tags.round = value,
+                "White" => tags.white = value,
+                "Black" => tags.black = value,
+                "Result" => result = parse_result(&value).flatten(),
+                "FEN" => start = Position::from_fen(&value).map_err(PgnError::Fen)?,
+                _ => {}
+            }
+        }
+
+        let movetext: Vec<&str> = lines.collect();
+        let movetext = movetext.join("\n");
+        let mut position = start.clone();
+        let mut moves = Vec::new();
+        let mut chars = movetext.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' => {
+                    chars.by_ref().find(|c| *c == '}');
+                }
+                ';' => {
+                    chars.by_ref().find(|c| *c == '\n');
+                }
+                // variations can nest, none of their moves belong to the game
+                '(' => {
+                    let mut depth = 1;
+                    for c in chars.by_ref() {
+                        match c {
+                            '(' => depth += 1,
+                            ')' => depth -= 1,
+                            _ => {}
+                        }
+                        if depth == 0 {
+                            break;
+                        }
+                    }
+                }
+                // the next game's tags, when this one lacks its result
+                '[' => break,
+                c if c.is_whitespace() => {}
+                c => {
+                    let mut token = c.to_string();
+                    while let Some(next) =
+                        chars.next_if(|next| !next.is_whitespace() && !"{;([".contains(*next))
+                    {
+                        token.push(next);
+                    }
+
+                    if let Some(game_result) = parse_result(&token) {
+                        result = game_result;
+                        break;
+                    }
+                    if token.starts_with('$') {
+                        continue;
+                    }
+
+                    // move numbers like `12.` or `12...`, which may be glued to the move
+                    let san = match token.rfind('.') {
+                        Some(dot) => &token[dot + 1..],
+                        None => &token,
+                    };
+                    if san.is_empty() {
+                        continue;
+                    }
+
+                    let mv = position.parse_san(san).map_err(|error| PgnError::Move {
+                        ply: moves.len() + 1,
+                        error,
+                    })?;
+                    position.apply(mv);
+                    moves.push(mv);
+                }
+            }
+        }
+
+        Ok(Self {
+            tags,
+            start,
+            moves,
+            clocks: Vec::new(),
+            result,
+        })
+    }
+
+    /// Reads every game of a PGN collection, in order. A game that can't be read doesn't
+    /// keep the ones after it from being read.
+    pub fn parse_all(pgn: &str) -> Vec<Result<Self, PgnError>> {
+        let mut games = Vec::new();
+        let mut game = String::new();
+        let mut in_movetext = false;
+
+        for line in pgn.lines() {
+            let tag = line.trim_start().starts_with('[');
+            // a tag after the movetext starts the next game
+            if tag && in_movetext {
+                games.push(Self::parse(&game));
+                game.clear();
+            }
+            in_movetext = match tag {
+                true => false,
+                false => in_movetext || !line.trim().is_empty(),
+            };
+
+            game.push_str(line);
+            game.push('\n');
+        }
+        if !game.trim().is_empty() {
+            games.push(Self::parse(&game));
+        }
+
+        games
+    }
+}
+
+impl FromStr for Pgn {
+    type Err = PgnError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
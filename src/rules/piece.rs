@@ -0,0 +1,108 @@
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Team {
+    White,
+    Black,
+}
+
+impl Team {
+    pub fn opponent(self) -> Self {
+        match self {
+            Self::White => Self::Black,
+            Self::Black => Self::White,
+        }
+    }
+
+    // position of the team in per-team tables
+    pub(crate) fn index(self) -> usize {
+        match self {
+            Self::White => 0,
+            Self::Black => 1,
+        }
+    }
+
+    // the rank the pieces of this team start on
+    pub(crate) fn back_rank(self) -> u8 {
+        match self {
+            Self::White => 0,
+            Self::Black => 7,
+        }
+    }
+
+    // the rank pawns of this team promote on
+    pub(crate) fn last_rank(self) -> u8 {
+        self.opponent().back_rank()
+    }
+
+    // the direction pawns of this team advance in
+    pub(crate) fn forward(self) -> i8 {
+        match self {
+            Self::White => 1,
+            Self::Black => -1,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum PieceKind {
+    Pawn,
+    Rock,
+    Knight,
+    Bishop,
+    Queen,
+    King,
+}
+
+impl PieceKind {
+    /// What a pawn can turn into on the last rank, best first.
+    pub const PROMOTIONS: [PieceKind; 4] = [
+        PieceKind::Queen,
+        PieceKind::Rock,
+        PieceKind::Bishop,
+        PieceKind::Knight,
+    ];
+
+    pub const ALL: [PieceKind; 6] = [
+        PieceKind::Pawn,
+        PieceKind::Rock,
+        PieceKind::Knight,
+        PieceKind::Bishop,
+        PieceKind::Queen,
+        PieceKind::King,
+    ];
+
+    // position of the kind in per-kind tables
+    pub(crate) fn index(self) -> usize {
+        match self {
+            Self::Pawn => 0,
+            Self::Rock => 1,
+            Self::Knight => 2,
+            Self::Bishop => 3,
+            Self::Queen => 4,
+            Self::King => 5,
+        }
+    }
+
+    /// The uppercase letter used for this kind in chess notation.
+    pub fn symbol(self) -> char {
+        match self {
+            Self::Pawn => 'P',
+            Self::Rock => 'R',
+            Self::Knight => 'N',
+            Self::Bishop => 'B',
+            Self::Queen => 'Q',
+            Self::King => 'K',
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Piece {
+    pub kind: PieceKind,
+    pub team: Team,
+}
+
+impl Piece {
+    pub fn new(kind: PieceKind, team: Team) -> Self {
+        Self { kind, team }
+    }
+}
@@ -0,0 +1,207 @@
+use super::{
+    attacks, movegen,
+    zobrist::{castling_key, piece_key, side_key},
+    Board, CastlingRights, Move, Piece, PieceKind, Square, Team,
+};
+
+/// A board together with whose turn it is, the castles still available, the square a
+/// pawn can be taken en passant on and the move counters, everything a FEN string holds.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Position {
+    board: Board,
+    side_to_move: Team,
+    castling: CastlingRights,
+    // the square skipped by the last double pawn push
+    en_passant: Option<Square>,
+    // half-moves since the last capture or pawn move, for the fifty-move rule
+    halfmove_clock: u32,
+    // starts at 1 and goes up after every move of Black
+    fullmove_number: u32,
+    // the Zobrist key, updated along with everything above
+    hash: u64,
+}
+
+impl Position {
+    /// A position with no castling rights, see [`Position::set_castling_rights`].
+    pub fn new(board: Board, side_to_move: Team) -> Self {
+        let mut position = Self {
+            board,
+            side_to_move,
+            castling: CastlingRights::none(),
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            hash: 0,
+        };
+        position.hash = position.full_zobrist();
+
+        position
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn side_to_move(&self) -> Team {
+        self.side_to_move
+    }
+
+    pub fn castling_rights(&self) -> CastlingRights {
+        self.castling
+    }
+
+    pub fn set_castling_rights(&mut self, castling: CastlingRights) {
+        self.hash ^= castling_key(self.castling) ^ castling_key(castling);
+        self.castling = castling;
+    }
+
+    /// The square a pawn that just advanced two tiles skipped, where it can be captured
+    /// en passant during this turn only.
+    pub fn en_passant(&self) -> Option<Square> {
+        self.en_passant
+    }
+
+    pub fn set_en_passant(&mut self, en_passant: Option<Square>) {
+        self.hash ^= self.en_passant_key();
+        self.en_passant = en_passant;
+        self.hash ^= self.en_passant_key();
+    }
+
+    pub fn halfmove_clock(&self) -> u32 {
+        self.halfmove_clock
+    }
+
+    pub fn set_halfmove_clock(&mut self, halfmove_clock: u32) {
+        self.halfmove_clock = halfmove_clock;
+    }
+
+    pub fn fullmove_number(&self) -> u32 {
+        self.fullmove_number
+    }
+
+    pub fn set_fullmove_number(&mut self, fullmove_number: u32) {
+        self.fullmove_number = fullmove_number;
+    }
+
+    /// The Zobrist key of the position, equal for positions with the same pieces, side to
+    /// move, castling rights and en passant capture. It's kept up to date as moves are
+    /// played, so reading it is free.
+    pub fn zobrist(&self) -> u64 {
+        self.hash
+    }
+
+    /// Where the pawn taken by `mv` stands when it's an en passant capture, which isn't the
+    /// tile the capturing pawn lands on.
+    pub fn en_passant_capture(&self, mv: Move) -> Option<Square> {
+        let piece = self.board.get(mv.from)?;
+
+        if piece.kind == PieceKind::Pawn
+            && piece.team == self.side_to_move
+            && Some(mv.to) == self.en_passant
+            && mv.from.file() != mv.to.file()
+        {
+            Some(Square::new(mv.to.file(), mv.from.rank()))
+        } else {
+            None
+        }
+    }
+
+    /// Whether the king of the side to move is attacked.
+    pub fn in_check(&self) -> bool {
+        self.is_in_check(self.side_to_move)
+    }
+
+    /// Whether the king of `team` is attacked, a board without that king is never in check.
+    pub fn is_in_check(&self, team: Team) -> bool {
+        match self.board.king_square(team) {
+            Some(king) => attacks::is_attacked(&self.board, king, team.opponent()),
+            None => false,
+        }
+    }
+
+    /// Every move the side to move can play.
+    pub fn legal_moves(&self) -> Vec<Move> {
+        movegen::legal_moves(self)
+    }
+
+    /// The moves available to whatever piece stands on `from`, whichever side it belongs to.
+    pub fn moves_from(&self, from: Square) -> Vec<Move> {
+        movegen::moves_from(self, from)
+    }
+
+    /// Plays `mv` and hands the turn over, returning the captured piece if there was one.
+    ///
+    /// The move isn't validated, callers are expected to pick it from the generated moves.
+    /// There's no way to take it back, to look at a move without playing it apply it to a
+    /// clone.
+    pub fn apply(&mut self, mv: Move) -> Option<Piece> {
+        let piece = self.board.get(mv.from);
+        let mut captured = self.board.get(mv.to);
+        // what depends on more than the pieces is taken out now and put back at the end
+        self.hash ^= self.en_passant_key() ^ castling_key(self.castling);
+
+        if let Some(bypassed) = self.en_passant_capture(mv) {
+            captured = self.board.get(bypassed);
+            self.put(bypassed, None);
+        }
+
+        if let Some(rook) = self.castling_rook(mv) {
+            let rook_piece = self.board.get(rook.from);
+            self.put(rook.from, None);
+            self.put(rook.to, rook_piece);
+        }
+
+        self.put(mv.from, None);
+        self.put(
+            mv.to,
+            match (piece, mv.promotion) {
+                (Some(pawn), Some(kind)) => Some(Piece::new(kind, pawn.team)),
+                _ => piece,
+            },
+        );
+        self.halfmove_clock = match piece {
+            Some(pawn) if pawn.kind == PieceKind::Pawn => 0,
+            _ if captured.is_some() => 0,
+            _ => self.halfmove_clock + 1,
+        };
+        self.castling.update(mv);
+        self.en_passant = match piece {
+            Some(pawn)
+                if pawn.kind == PieceKind::Pawn && mv.from.rank().abs_diff(mv.to.rank()) == 2 =>
+            {
+                Some(Square::new(
+                    mv.from.file(),
+                    (mv.from.rank() + mv.to.rank()) / 2,
+                ))
+            }
+            _ => None,
+        };
+        if self.side_to_move == Team::Black {
+            self.fullmove_number += 1;
+        }
+        self.side_to_move = self.side_to_move.opponent();
+        self.hash ^= side_key(Team::Black) ^ self.en_passant_key() ^ castling_key(self.castling);
+
+        captured
+    }
+
+    // sets the piece on a square, keeping the key in step
+    fn put(&mut self, square: Square, piece: Option<Piece>) {
+        if let Some(old) = self.board.get(square) {
+            self.hash ^= piece_key(old, square);
+        }
+        if let Some(new) = piece {
+            self.hash ^= piece_key(new, square);
+        }
+
+        self.board.set(square, piece);
+    }
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        let mut position = Self::new(Board::standard(), Team::White);
+        position.set_castling_rights(CastlingRights::all());
+        position
+    }
+}
@@ -0,0 +1,210 @@
+use std::fmt;
+
+use super::{CastleSide, Move, PieceKind, Position, Square};
+
+impl Position {
+    /// Writes `mv` in Standard Algebraic Notation, like `Nbd7`, `exd6`, `O-O-O` or `e8=Q+`.
+    ///
+    /// The move has to be legal in this position, the piece is found on its `from` square.
+    pub fn san(&self, mv: Move) -> String {
+        let piece = match self.board().get(mv.from) {
+            Some(piece) => piece,
+            None => return mv.to_string(),
+        };
+        let capture = self.board().get(mv.to).is_some() || self.en_passant_capture(mv).is_some();
+
+        let mut san = match self.castle_side(mv) {
+            Some(CastleSide::King) => "O-O".to_string(),
+            Some(CastleSide::Queen) => "O-O-O".to_string(),
+            // pawns are named by their file, and only when they capture
+            None if piece.kind == PieceKind::Pawn => {
+                let mut san = String::new();
+                if capture {
+                    san.push((b'a' + mv.from.file()) as char);
+                    san.push('x');
+                }
+                san.push_str(&mv.to.to_string());
+                if let Some(kind) = mv.promotion {
+                    san.push('=');
+                    san.push(kind.symbol());
+                }
+                san
+            }
+            None => {
+                let mut san = piece.kind.symbol().to_string();
+                san.push_str(&self.disambiguation(mv));
+                if capture {
+                    san.push('x');
+                }
+                san.push_str(&mv.to.to_string());
+                san
+            }
+        };
+
+        let mut after = self.clone();
+        after.apply(mv);
+        if after.in_check() {
+            san.push(if after.legal_moves().is_empty() {
+                '#'
+            } else {
+                '+'
+            });
+        }
+
+        san
+    }
+
+    // when another piece of the same kind can reach the same square, the file of the moving
+    // piece tells them apart, or its rank if they share the file, or both if neither does
+    fn disambiguation(&self, mv: Move) -> String {
+        let kind = self.board().get(mv.from).map(|piece| piece.kind);
+        let rivals: Vec<Move> = self
+            .legal_moves()
+            .into_iter()
+            .filter(|other| {
+                other.to == mv.to
+                    && other.from != mv.from
+                    && self.board().get(other.from).map(|piece| piece.kind) == kind
+            })
+            .collect();
+
+        let from = mv.from.to_string();
+        if rivals.is_empty() {
+            String::new()
+        } else if rivals
+            .iter()
+            .all(|other| other.from.file() != mv.from.file())
+        {
+            from[..1].to_string()
+        } else if rivals
+            .iter()
+            .all(|other| other.from.rank() != mv.from.rank())
+        {
+            from[1..].to_string()
+        } else {
+            from
+        }
+    }
+}
+
+/// Why a move in algebraic notation doesn't match a move of the position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SanError {
+    /// The text isn't a move at all.
+    Invalid(String),
+    /// No legal move fits the notation.
+    Illegal(String),
+    /// More than one legal move fits, the notation is missing a disambiguation.
+    Ambiguous(String),
+}
+
+impl fmt::Display for SanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Invalid(san) => write!(f, "{:?} isn't a move", san),
+            Self::Illegal(san) => write!(f, "{:?} isn't legal in this position", san),
+            Self::Ambiguous(san) => write!(f, "{:?} could be more than one move", san),
+        }
+    }
+}
+
+impl std::error::Error for SanError {}
+
+fn kind_from_symbol(symbol: char) -> Option<PieceKind> {
+    [
+        PieceKind::Rock,
+        PieceKind::Knight,
+        PieceKind::Bishop,
+        PieceKind::Queen,
+        PieceKind::King,
+    ]
+    .into_iter()
+    .find(|kind| kind.symbol() == symbol)
+}
+
+impl Position {
+    /// Reads a move in Standard Algebraic Notation and finds it among the legal moves,
+    /// resolving which piece moves from the disambiguation, if any.
+    ///
+    /// Check and annotation suffixes are ignored, as are an `e.p.` mark and `0-0` written
+    /// with zeros.
+    pub fn parse_san(&self, san: &str) -> Result<Move, SanError> {
+        let invalid = || SanError::Invalid(san.to_string());
+        let text = san
+            .trim()
+            .trim_end_matches("e.p.")
+            .trim_end()
+            .trim_end_matches(['+', '#', '!', '?']);
+
+        let castle = match text {
+            "O-O" | "0-0" => Some(CastleSide::King),
+            "O-O-O" | "0-0-0" => Some(CastleSide::Queen),
+            _ => None,
+        };
+        if let Some(side) = castle {
+            let mv = side.king_move(self.side_to_move());
+            return match self.legal_moves().contains(&mv) {
+                true => Ok(mv),
+                false => Err(SanError::Illegal(san.to_string())),
+            };
+        }
+
+        // the piece letter, which pawns don't have
+        let mut chars: Vec<char> = text.chars().collect();
+        let kind = match chars.first().copied().and_then(kind_from_symbol) {
+            Some(kind) => {
+                chars.remove(0);
+                kind
+            }
+            None => PieceKind::Pawn,
+        };
+
+        // `e8=Q`, also taken without the equals sign
+        let promotion = match chars.last().copied().and_then(kind_from_symbol) {
+            Some(promotion) => {
+                chars.pop();
+                if chars.last() == Some(&'=') {
+                    chars.pop();
+                }
+                Some(promotion)
+            }
+            None => None,
+        };
+
+        if chars.len() < 2 {
+            return Err(invalid());
+        }
+        let to: String = chars.split_off(chars.len() - 2).into_iter().collect();
+        let to: Square = to.parse().map_err(|_| invalid())?;
+
+        // what's left is the disambiguation and the capture mark
+        let mut file = None;
+        let mut rank = None;
+        for c in chars.into_iter().filter(|c| *c != 'x' && *c != ':') {
+            match c {
+                'a'..='h' if file.is_none() && rank.is_none() => file = Some(c as u8 - b'a'),
+                '1'..='8' if rank.is_none() => rank = Some(c as u8 - b'1'),
+                _ => return Err(invalid()),
+            }
+        }
+
+        let candidates: Vec<Move> = self
+            .legal_moves()
+            .into_iter()
+            .filter(|mv| {
+                mv.to == to
+                    && mv.promotion == promotion
+                    && self.castle_side(*mv).is_none()
+                    && matches!(self.board().get(mv.from), Some(piece) if piece.kind == kind)
+                    && (file.is_none() || file == Some(mv.from.file()))
+                    && (rank.is_none() || rank == Some(mv.from.rank()))
+            })
+            .collect();
+
+        match candidates.as_slice() {
+            [mv] => Ok(*mv),
+            [] => Err(SanError::Illegal(san.to_string())),
+            _ => Err(SanError::Ambiguous(san.to_string())),
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use std::{fmt, str::FromStr};
+
+/// One of the 64 squares of the board, `a1` is 0 and `h8` is 63.
+///
+/// Files map to the tilemap `x` and ranks to `y`, so White starts on ranks 0 and 1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Square(u8);
+
+impl Square {
+    pub fn new(file: u8, rank: u8) -> Self {
+        assert!(
+            file < 8 && rank < 8,
+            "square ({file}, {rank}) is off the board"
+        );
+        Self(rank * 8 + file)
+    }
+
+    pub fn from_index(index: usize) -> Self {
+        assert!(index < 64, "square index {index} is off the board");
+        Self(index as u8)
+    }
+
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+
+    pub fn file(self) -> u8 {
+        self.0 % 8
+    }
+
+    pub fn rank(self) -> u8 {
+        self.0 / 8
+    }
+
+    /// The square `files` and `ranks` away from this one, if it's still on the board.
+    pub fn offset(self, files: i8, ranks: i8) -> Option<Self> {
+        let file = self.file() as i8 + files;
+        let rank = self.rank() as i8 + ranks;
+
+        if (0..8).contains(&file) && (0..8).contains(&rank) {
+            Some(Self::new(file as u8, rank as u8))
+        } else {
+            None
+        }
+    }
+
+    pub fn all() -> impl Iterator<Item = Square> {
+        (0..64).map(Square)
+    }
+}
+
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", (b'a' + self.file()) as char, self.rank() + 1)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseSquareError(pub String);
+
+impl fmt::Display for ParseSquareError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid square: {:?}", self.0)
+    }
+}
+
+impl std::error::Error for ParseSquareError {}
+
+impl FromStr for Square {
+    type Err = ParseSquareError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.as_bytes() {
+            [file @ b'a'..=b'h', rank @ b'1'..=b'8'] => Ok(Self::new(file - b'a', rank - b'1')),
+            _ => Err(ParseSquareError(s.to_string())),
+        }
+    }
+}
@@ -0,0 +1,125 @@
+//! Zobrist keys: every piece on every square, every castling right, every en passant file
+//! and Black being on move get a random 64-bit number, and a position's key is the xor of
+//! the numbers of everything in it. Playing a move only has to xor out what changed and
+//! xor in what replaced it. Moves are never taken back: positions are small enough to copy,
+//! so the search and the legality checks play each move on a copy and keep the original.
+
+use super::{
+    bitboard::pawn_attacks, CastleSide, CastlingRights, Piece, PieceKind, Position, Square, Team,
+};
+
+struct Keys {
+    pieces: [[[u64; 64]; 6]; 2],
+    castling: [[u64; 2]; 2],
+    en_passant: [u64; 8],
+    black_to_move: u64,
+}
+
+static KEYS: Keys = keys();
+
+// splitmix64, the seed only has to stay the same between builds for the keys to match
+const fn next(state: u64) -> (u64, u64) {
+    let state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    (state, z ^ (z >> 31))
+}
+
+const fn keys() -> Keys {
+    let mut keys = Keys {
+        pieces: [[[0; 64]; 6]; 2],
+        castling: [[0; 2]; 2],
+        en_passant: [0; 8],
+        black_to_move: 0,
+    };
+    let mut state = 0x5eed;
+    let mut key;
+
+    let mut team = 0;
+    while team < 2 {
+        let mut kind = 0;
+        while kind < 6 {
+            let mut square = 0;
+            while square < 64 {
+                (state, key) = next(state);
+                keys.pieces[team][kind][square] = key;
+                square += 1;
+            }
+            kind += 1;
+        }
+        team += 1;
+    }
+
+    let mut right = 0;
+    while right < 4 {
+        (state, key) = next(state);
+        keys.castling[right / 2][right % 2] = key;
+        right += 1;
+    }
+
+    let mut file = 0;
+    while file < 8 {
+        (state, key) = next(state);
+        keys.en_passant[file] = key;
+        file += 1;
+    }
+
+    (_, keys.black_to_move) = next(state);
+    keys
+}
+
+pub(super) fn piece_key(piece: Piece, square: Square) -> u64 {
+    KEYS.pieces[piece.team.index()][piece.kind.index()][square.index()]
+}
+
+pub(super) fn castling_key(rights: CastlingRights) -> u64 {
+    let mut key = 0;
+
+    for team in [Team::White, Team::Black] {
+        for (i, side) in [CastleSide::King, CastleSide::Queen]
+            .into_iter()
+            .enumerate()
+        {
+            if rights.has(team, side) {
+                key ^= KEYS.castling[team.index()][i];
+            }
+        }
+    }
+
+    key
+}
+
+pub(super) fn side_key(side_to_move: Team) -> u64 {
+    match side_to_move {
+        Team::White => 0,
+        Team::Black => KEYS.black_to_move,
+    }
+}
+
+impl Position {
+    // the en passant file only counts when a pawn of the side to move stands ready to take,
+    // otherwise the skipped square makes no difference to the position
+    pub(super) fn en_passant_key(&self) -> u64 {
+        match self.en_passant() {
+            Some(square)
+                if pawn_attacks(self.side_to_move().opponent(), square)
+                    & self.board().pieces_of(PieceKind::Pawn, self.side_to_move())
+                    != 0 =>
+            {
+                KEYS.en_passant[square.file() as usize]
+            }
+            _ => 0,
+        }
+    }
+
+    // the key worked out from scratch, which moves then keep up to date
+    pub(super) fn full_zobrist(&self) -> u64 {
+        self.board()
+            .pieces()
+            .fold(0, |key, (square, piece)| key ^ piece_key(piece, square))
+            ^ castling_key(self.castling_rights())
+            ^ self.en_passant_key()
+            ^ side_key(self.side_to_move())
+    }
+}
@@ -0,0 +1,291 @@
+//! The Universal Chess Interface, the text protocol chess GUIs and tournament managers use
+//! to talk to engines: one command per line on the engine's stdin, answers on its stdout.
+//!
+//! Both sides are here: the commands a GUI sends are parsed for the `uci` binary, and
+//! [`EngineProcess`] sends them to another engine to play against it.
+
+mod process;
+
+use std::{fmt, str::FromStr, time::Duration};
+
+use crate::{
+    engine::{mate_in, SearchLimits},
+    rules::{FenError, Move, ParseMoveError, Position, Team},
+};
+
+pub use process::{EngineError, EngineProcess, EngineStopper};
+
+/// A line sent to the engine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Asks the engine to identify itself and list its options.
+    Uci,
+    /// Asks the engine to answer once it's done with everything sent before.
+    IsReady,
+    SetOption {
+        name: String,
+        value: Option<String>,
+    },
+    /// The next position comes from a different game.
+    UciNewGame,
+    /// The position to search next, with the moves that led to it already played.
+    Position(Box<Position>),
+    Go(Go),
+    /// Ends the running search, its best move still has to be sent.
+    Stop,
+    Quit,
+}
+
+/// How long to search for, `go` and its arguments.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Go {
+    pub white_time: Option<Duration>,
+    pub black_time: Option<Duration>,
+    pub white_increment: Option<Duration>,
+    pub black_increment: Option<Duration>,
+    /// Moves left until the next time control, sudden death when it's not given.
+    pub moves_to_go: Option<u32>,
+    pub depth: Option<u32>,
+    pub nodes: Option<u64>,
+    /// Search exactly this long.
+    pub move_time: Option<Duration>,
+    /// Search until told to stop.
+    pub infinite: bool,
+}
+
+impl Go {
+    /// The limits of a search for `side`, which only looks at its own clock.
+    pub fn limits(&self, side: Team) -> SearchLimits {
+        if self.infinite {
+            return SearchLimits::default();
+        }
+
+        let (time, increment) = match side {
+            Team::White => (self.white_time, self.white_increment),
+            Team::Black => (self.black_time, self.black_increment),
+        };
+        let mut limits = match (self.move_time, time) {
+            (Some(move_time), _) => SearchLimits::time(move_time),
+            (None, Some(time)) => {
+                SearchLimits::from_clock(time, increment.unwrap_or_default(), self.moves_to_go)
+            }
+            (None, None) => SearchLimits::default(),
+        };
+        limits.depth = self.depth;
+        limits.nodes = self.nodes;
+
+        limits
+    }
+}
+
+/// The `go` command as it's sent to an engine.
+impl fmt::Display for Go {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "go")?;
+
+        let times = [
+            ("wtime", self.white_time),
+            ("btime", self.black_time),
+            ("winc", self.white_increment),
+            ("binc", self.black_increment),
+            ("movetime", self.move_time),
+        ];
+        for (name, time) in times {
+            if let Some(time) = time {
+                write!(f, " {} {}", name, time.as_millis())?;
+            }
+        }
+        if let Some(moves_to_go) = self.moves_to_go {
+            write!(f, " movestogo {}", moves_to_go)?;
+        }
+        if let Some(depth) = self.depth {
+            write!(f, " depth {}", depth)?;
+        }
+        if let Some(nodes) = self.nodes {
+            write!(f, " nodes {}", nodes)?;
+        }
+        if self.infinite {
+            write!(f, " infinite")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The `position` command for the game that started from `start` with `moves` played
+/// since. Sending the moves rather than the position they lead to lets the engine see
+/// repetitions.
+pub fn position_command(start: &Position, moves: &[Move]) -> String {
+    let mut command = match *start == Position::default() {
+        true => "position startpos".to_string(),
+        false => format!("position fen {}", start.to_fen()),
+    };
+
+    if !moves.is_empty() {
+        command.push_str(" moves");
+        for mv in moves {
+            command.push_str(&format!(" {}", mv));
+        }
+    }
+
+    command
+}
+
+/// Why a line isn't a command the engine can follow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UciError {
+    /// The line doesn't start with a known command.
+    Unknown(String),
+    /// A command is missing one of its parts.
+    Missing(&'static str),
+    /// An argument has a value that can't be read, like `depth x`.
+    Value {
+        name: String,
+        value: String,
+    },
+    Fen(FenError),
+    Move(ParseMoveError),
+}
+
+impl fmt::Display for UciError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Unknown(line) => write!(f, "unknown command: {}", line),
+            Self::Missing(part) => write!(f, "missing {}", part),
+            Self::Value { name, value } => write!(f, "invalid {}: {:?}", name, value),
+            Self::Fen(err) => write!(f, "invalid fen: {}", err),
+            Self::Move(err) => write!(f, "invalid move: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for UciError {}
+
+// `position [startpos | fen <fen>] [moves <move>...]`
+fn parse_position<'a>(mut words: impl Iterator<Item = &'a str>) -> Result<Position, UciError> {
+    let mut position = match words.next() {
+        Some("startpos") => Position::default(),
+        Some("fen") => {
+            let fen = words
+                .by_ref()
+                .take_while(|&word| word != "moves")
+                .collect::<Vec<_>>()
+                .join(" ");
+            // the fen took the `moves` word with it
+            let position = Position::from_fen(&fen).map_err(UciError::Fen)?;
+            return play_moves(position, words);
+        }
+        _ => return Err(UciError::Missing("startpos or fen")),
+    };
+
+    match words.next() {
+        Some("moves") => {
+            position = play_moves(position, words)?;
+        }
+        Some(word) => return Err(UciError::Unknown(word.to_string())),
+        None => {}
+    }
+
+    Ok(position)
+}
+
+fn play_moves<'a>(
+    mut position: Position,
+    moves: impl Iterator<Item = &'a str>,
+) -> Result<Position, UciError> {
+    for uci in moves {
+        let mv = position.parse_uci(uci).map_err(UciError::Move)?;
+        position.apply(mv);
+    }
+
+    Ok(position)
+}
+
+// the arguments of `go`, a name and usually a number after it
+fn parse_go<'a>(mut words: impl Iterator<Item = &'a str>) -> Result<Go, UciError> {
+    let mut go = Go::default();
+
+    while let Some(name) = words.next() {
+        if name == "infinite" {
+            go.infinite = true;
+            continue;
+        }
+        // pondering isn't supported, the rest is searched as usual
+        if name == "ponder" {
+            continue;
+        }
+
+        let value = words.next().ok_or(UciError::Missing("a value"))?;
+        let invalid = || UciError::Value {
+            name: name.to_string(),
+            value: value.to_string(),
+        };
+        let millis = || {
+            value
+                .parse()
+                .map(Duration::from_millis)
+                .map_err(|_| invalid())
+        };
+
+        match name {
+            "wtime" => go.white_time = Some(millis()?),
+            "btime" => go.black_time = Some(millis()?),
+            "winc" => go.white_increment = Some(millis()?),
+            "binc" => go.black_increment = Some(millis()?),
+            "movetime" => go.move_time = Some(millis()?),
+            "movestogo" => go.moves_to_go = Some(value.parse().map_err(|_| invalid())?),
+            "depth" => go.depth = Some(value.parse().map_err(|_| invalid())?),
+            "nodes" => go.nodes = Some(value.parse().map_err(|_| invalid())?),
+            _ => return Err(UciError::Unknown(name.to_string())),
+        }
+    }
+
+    Ok(go)
+}
+
+// `setoption name <name> [value <value>]`, both can have spaces in them
+fn parse_set_option<'a>(mut words: impl Iterator<Item = &'a str>) -> Result<Command, UciError> {
+    if words.next() != Some("name") {
+        return Err(UciError::Missing("name"));
+    }
+
+    let name = words
+        .by_ref()
+        .take_while(|&word| word != "value")
+        .collect::<Vec<_>>()
+        .join(" ");
+    let value = words.collect::<Vec<_>>().join(" ");
+
+    Ok(Command::SetOption {
+        name,
+        value: (!value.is_empty()).then_some(value),
+    })
+}
+
+impl FromStr for Command {
+    type Err = UciError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let mut words = line.split_whitespace();
+
+        match words.next() {
+            Some("uci") => Ok(Self::Uci),
+            Some("isready") => Ok(Self::IsReady),
+            Some("setoption") => parse_set_option(words),
+            Some("ucinewgame") => Ok(Self::UciNewGame),
+            Some("position") => Ok(Self::Position(Box::new(parse_position(words)?))),
+            Some("go") => Ok(Self::Go(parse_go(words)?)),
+            Some("stop") => Ok(Self::Stop),
+            Some("quit") => Ok(Self::Quit),
+            _ => Err(UciError::Unknown(line.to_string())),
+        }
+    }
+}
+
+/// A search score as `info` lines give it, `cp <centipawns>` or `mate <moves>`.
+pub fn score(score: i32) -> String {
+    match mate_in(score) {
+        Some(moves) => format!("mate {}", moves),
+        None => format!("cp {}", score),
+    }
+}
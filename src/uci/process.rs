@@ -0,0 +1,191 @@
+use std::{
+    ffi::OsStr,
+    fmt,
+    io::{self, BufRead, BufReader, Write},
+    process::{Child, ChildStdin, ChildStdout, Command as Process, Stdio},
+    sync::{Arc, Mutex, PoisonError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::rules::{Move, ParseMoveError, Position};
+
+use super::{position_command, Go};
+
+// how long the engine gets to quit on its own before it's killed
+const QUIT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Why talking to an engine failed.
+#[derive(Debug)]
+pub enum EngineError {
+    Io(io::Error),
+    /// The engine closed its output, it crashed or quit.
+    Closed,
+    /// The engine's best move isn't a legal move in the position it was given.
+    Move(ParseMoveError),
+}
+
+impl fmt::Display for EngineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "couldn't talk to the engine: {}", err),
+            Self::Closed => write!(f, "the engine stopped answering"),
+            Self::Move(err) => write!(f, "the engine played an invalid move: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for EngineError {}
+
+impl From<io::Error> for EngineError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+fn send(input: &Mutex<ChildStdin>, line: &str) -> Result<(), EngineError> {
+    let mut input = input.lock().unwrap_or_else(PoisonError::into_inner);
+    writeln!(input, "{}", line)?;
+    input.flush()?;
+    Ok(())
+}
+
+/// Stops the engine's search from another thread, while the one that started it waits for
+/// the best move.
+#[derive(Clone)]
+pub struct EngineStopper(Arc<Mutex<ChildStdin>>);
+
+impl EngineStopper {
+    /// The engine still answers with the best move it has found.
+    pub fn stop(&self) -> Result<(), EngineError> {
+        send(&self.0, "stop")
+    }
+}
+
+/// A UCI engine running as a child process, asked for moves the way a GUI would.
+pub struct EngineProcess {
+    child: Child,
+    input: Arc<Mutex<ChildStdin>>,
+    output: BufReader<ChildStdout>,
+    name: String,
+}
+
+impl EngineProcess {
+    /// Starts `program` with `args` and waits until it says it's ready.
+    pub fn spawn<S: AsRef<OsStr>>(program: S, args: &[String]) -> Result<Self, EngineError> {
+        let mut child = Process::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let input = child.stdin.take().ok_or(EngineError::Closed)?;
+        let output = child.stdout.take().ok_or(EngineError::Closed)?;
+
+        let mut engine = Self {
+            child,
+            input: Arc::new(Mutex::new(input)),
+            output: BufReader::new(output),
+            name: String::new(),
+        };
+
+        engine.send("uci")?;
+        loop {
+            let line = engine.read_line()?;
+            if let Some(name) = line.strip_prefix("id name ") {
+                engine.name = name.trim().to_string();
+            }
+            if line.trim() == "uciok" {
+                break;
+            }
+        }
+        engine.wait_until_ready()?;
+
+        Ok(engine)
+    }
+
+    /// What the engine calls itself, empty if it didn't say.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn stopper(&self) -> EngineStopper {
+        EngineStopper(self.input.clone())
+    }
+
+    fn send(&self, line: &str) -> Result<(), EngineError> {
+        send(&self.input, line)
+    }
+
+    fn read_line(&mut self) -> Result<String, EngineError> {
+        let mut line = String::new();
+        match self.output.read_line(&mut line)? {
+            0 => Err(EngineError::Closed),
+            _ => Ok(line),
+        }
+    }
+
+    fn wait_until_ready(&mut self) -> Result<(), EngineError> {
+        self.send("isready")?;
+        while self.read_line()?.trim() != "readyok" {}
+        Ok(())
+    }
+
+    pub fn set_option(&mut self, name: &str, value: &str) -> Result<(), EngineError> {
+        self.send(&format!("setoption name {} value {}", name, value))?;
+        self.wait_until_ready()
+    }
+
+    /// Tells the engine the next position is from another game.
+    pub fn new_game(&mut self) -> Result<(), EngineError> {
+        self.send("ucinewgame")?;
+        self.wait_until_ready()
+    }
+
+    /// Asks for the best move of the game that started from `start` and went on with
+    /// `moves`, searching as long as `go` says. `None` when the engine has no move to play.
+    pub fn best_move(
+        &mut self,
+        start: &Position,
+        moves: &[Move],
+        go: &Go,
+    ) -> Result<Option<Move>, EngineError> {
+        let mut position = start.clone();
+        for mv in moves {
+            position.apply(*mv);
+        }
+
+        self.send(&position_command(start, moves))?;
+        self.send(&go.to_string())?;
+
+        // `info` lines come first, the search is over with `bestmove <move> [ponder <move>]`
+        let best_move = loop {
+            let line = self.read_line()?;
+            let mut words = line.split_whitespace();
+            if words.next() == Some("bestmove") {
+                break words.next().unwrap_or("0000").to_string();
+            }
+        };
+
+        match best_move.as_str() {
+            "0000" | "(none)" => Ok(None),
+            uci => position.parse_uci(uci).map(Some).map_err(EngineError::Move),
+        }
+    }
+}
+
+impl Drop for EngineProcess {
+    // asks the engine to quit, and makes sure it does
+    fn drop(&mut self) {
+        let _ = self.send("quit");
+
+        let asked = Instant::now();
+        while asked.elapsed() < QUIT_TIMEOUT {
+            match self.child.try_wait() {
+                Ok(None) => thread::sleep(Duration::from_millis(10)),
+                _ => return,
+            }
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
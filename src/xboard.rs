@@ -0,0 +1,246 @@
+//! The Chess Engine Communication Protocol, which xboard and WinBoard speak: like UCI one
+//! command per line, but the engine keeps the game itself and is told the moves one at a
+//! time, and which side it plays.
+//!
+//! Only version 2 of the protocol is supported, moves come with `usermove` in front.
+
+use std::{fmt, str::FromStr, time::Duration};
+
+use crate::{
+    engine::{mate_in, SearchLimits},
+    rules::{FenError, GameResult, Position},
+};
+
+/// A line sent to the engine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// The interface speaks xboard, it comes first.
+    Xboard,
+    /// The protocol version the interface speaks, the engine answers with its features.
+    Protover(u32),
+    /// A feature the engine asked for is supported.
+    Accepted(String),
+    Rejected(String),
+    /// A new game from the standard position, the engine plays black.
+    New,
+    /// The engine plays neither side, it only follows the moves it's sent.
+    Force,
+    /// The engine plays the side to move, and starts thinking.
+    Go,
+    /// The opponent's move, in the same coordinate notation UCI uses.
+    UserMove(String),
+    Level(Level),
+    /// Think exactly this long on every move.
+    St(Duration),
+    /// Search no deeper than this many plies.
+    Sd(u32),
+    /// What's left on the engine's clock.
+    Time(Duration),
+    /// What's left on the opponent's clock.
+    Otim(Duration),
+    SetBoard(Box<Position>),
+    /// Answered with `pong` and the same number, once everything before it is done.
+    Ping(u32),
+    /// `?`, move now with the best move found so far.
+    MoveNow,
+    /// Show the search's thinking.
+    Post,
+    NoPost,
+    /// The game is over, `None` when it was stopped without a result.
+    Result {
+        result: Option<GameResult>,
+        comment: String,
+    },
+    Quit,
+}
+
+/// A conventional time control, `level <moves> <base> <increment>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Level {
+    /// The moves each side makes before the clock gets `base` again, `None` for the whole
+    /// game.
+    pub moves_per_session: Option<u32>,
+    pub base: Duration,
+    /// Added to the clock after every move.
+    pub increment: Duration,
+}
+
+/// How long the engine may think on a move, set by `level`, `st` and `sd`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TimeControl {
+    pub level: Option<Level>,
+    /// Set by `st`, which replaces the level.
+    pub move_time: Option<Duration>,
+    pub depth: Option<u32>,
+}
+
+impl TimeControl {
+    /// The limits of a search for move `fullmove_number`, with `remaining` on the engine's
+    /// clock when `time` has told it. Until then the whole base time is assumed.
+    pub fn limits(&self, remaining: Option<Duration>, fullmove_number: u32) -> SearchLimits {
+        let mut limits = match (self.move_time, self.level) {
+            (Some(move_time), _) => SearchLimits::time(move_time),
+            (None, Some(level)) => {
+                let moves_to_go = level.moves_per_session.map(|moves| {
+                    let played = fullmove_number.saturating_sub(1) % moves.max(1);
+                    moves - played
+                });
+                SearchLimits::from_clock(
+                    remaining.unwrap_or(level.base),
+                    level.increment,
+                    moves_to_go,
+                )
+            }
+            (None, None) => SearchLimits::default(),
+        };
+        limits.depth = self.depth;
+
+        limits
+    }
+}
+
+/// Why a line isn't a command the engine can follow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum XboardError {
+    /// The line doesn't start with a known command.
+    Unknown(String),
+    /// A command is missing one of its parts.
+    Missing(&'static str),
+    /// An argument has a value that can't be read, like `sd x`.
+    Value {
+        name: &'static str,
+        value: String,
+    },
+    Fen(FenError),
+}
+
+impl fmt::Display for XboardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Unknown(line) => write!(f, "unknown command: {}", line),
+            Self::Missing(part) => write!(f, "missing {}", part),
+            Self::Value { name, value } => write!(f, "invalid {}: {:?}", name, value),
+            Self::Fen(err) => write!(f, "invalid fen: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for XboardError {}
+
+// the next word parsed as a `T`, named `name` in errors
+fn value<'a, T: FromStr>(
+    words: &mut impl Iterator<Item = &'a str>,
+    name: &'static str,
+) -> Result<T, XboardError> {
+    let value = words.next().ok_or(XboardError::Missing(name))?;
+    value.parse().map_err(|_| XboardError::Value {
+        name,
+        value: value.to_string(),
+    })
+}
+
+// clocks are given in centiseconds
+fn centiseconds<'a>(
+    words: &mut impl Iterator<Item = &'a str>,
+    name: &'static str,
+) -> Result<Duration, XboardError> {
+    value(words, name).map(|centiseconds: u64| Duration::from_millis(centiseconds * 10))
+}
+
+// `level <moves> <minutes>[:<seconds>] <seconds>`, with 0 moves for the whole game
+fn parse_level<'a>(mut words: impl Iterator<Item = &'a str>) -> Result<Level, XboardError> {
+    let moves: u32 = value(&mut words, "moves")?;
+
+    let base = words.next().ok_or(XboardError::Missing("base time"))?;
+    let invalid = || XboardError::Value {
+        name: "base time",
+        value: base.to_string(),
+    };
+    let (minutes, seconds) = base.split_once(':').unwrap_or((base, "0"));
+    let minutes: u64 = minutes.parse().map_err(|_| invalid())?;
+    let seconds: u64 = seconds.parse().map_err(|_| invalid())?;
+
+    let increment: f64 = value(&mut words, "increment")?;
+    let increment = Duration::try_from_secs_f64(increment).map_err(|_| XboardError::Value {
+        name: "increment",
+        value: increment.to_string(),
+    })?;
+
+    Ok(Level {
+        moves_per_session: (moves > 0).then_some(moves),
+        base: Duration::from_secs(minutes * 60 + seconds),
+        increment,
+    })
+}
+
+// `result <result> {<comment>}`
+fn parse_result<'a>(mut words: impl Iterator<Item = &'a str>) -> Result<Command, XboardError> {
+    let result = match words.next() {
+        Some("1-0") => Some(GameResult::WhiteWins),
+        Some("0-1") => Some(GameResult::BlackWins),
+        Some("1/2-1/2") => Some(GameResult::Draw),
+        Some("*") => None,
+        Some(result) => {
+            return Err(XboardError::Value {
+                name: "result",
+                value: result.to_string(),
+            })
+        }
+        None => return Err(XboardError::Missing("result")),
+    };
+    let comment = words.collect::<Vec<_>>().join(" ");
+
+    Ok(Command::Result {
+        result,
+        comment: comment.trim_matches(|c| c == '{' || c == '}').to_string(),
+    })
+}
+
+impl FromStr for Command {
+    type Err = XboardError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let mut words = line.split_whitespace();
+
+        match words.next() {
+            Some("xboard") => Ok(Self::Xboard),
+            Some("protover") => Ok(Self::Protover(value(&mut words, "version")?)),
+            Some("accepted") => Ok(Self::Accepted(words.collect::<Vec<_>>().join(" "))),
+            Some("rejected") => Ok(Self::Rejected(words.collect::<Vec<_>>().join(" "))),
+            Some("new") => Ok(Self::New),
+            Some("force") => Ok(Self::Force),
+            Some("go") => Ok(Self::Go),
+            Some("usermove") => Ok(Self::UserMove(value(&mut words, "move")?)),
+            Some("level") => Ok(Self::Level(parse_level(words)?)),
+            Some("st") => {
+                let seconds: u64 = value(&mut words, "seconds")?;
+                Ok(Self::St(Duration::from_secs(seconds)))
+            }
+            Some("sd") => Ok(Self::Sd(value(&mut words, "depth")?)),
+            Some("time") => Ok(Self::Time(centiseconds(&mut words, "time")?)),
+            Some("otim") => Ok(Self::Otim(centiseconds(&mut words, "time")?)),
+            Some("setboard") => {
+                let fen = words.collect::<Vec<_>>().join(" ");
+                let position = Position::from_fen(&fen).map_err(XboardError::Fen)?;
+                Ok(Self::SetBoard(Box::new(position)))
+            }
+            Some("ping") => Ok(Self::Ping(value(&mut words, "number")?)),
+            Some("?") => Ok(Self::MoveNow),
+            Some("post") => Ok(Self::Post),
+            Some("nopost") => Ok(Self::NoPost),
+            Some("result") => parse_result(words),
+            Some("quit") => Ok(Self::Quit),
+            _ => Err(XboardError::Unknown(line.to_string())),
+        }
+    }
+}
+
+/// A search score as thinking output gives it, in centipawns, with mates as 100000 plus
+/// the moves to mate, negative when the engine is the one getting mated.
+pub fn score(score: i32) -> i32 {
+    match mate_in(score) {
+        Some(moves) if moves > 0 => 100_000 + moves,
+        Some(moves) => -100_000 + moves,
+        None => score,
+    }
+}
@@ -0,0 +1,444 @@
+use std::{
+    sync::atomic::AtomicBool,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use bevy_chess::{
+    engine::{
+        evaluate, search, search_with_limits, see, Book, BookError, BookSelection, Bound, Entry,
+        EvalWeights, Phased, PolyglotKeys, SearchLimits, TranspositionTable, WeightsError, MATE,
+    },
+    rules::{Move, Pgn, Position, Square},
+};
+
+fn sq(name: &str) -> Square {
+    name.parse().unwrap()
+}
+
+#[test]
+fn material_is_scored_for_the_side_to_move() -> Result<()> {
+    let weights = EvalWeights::default();
+    assert_eq!(evaluate(&Position::default(), &weights), 0);
+
+    // white is a queen up
+    let white = Position::from_fen("4k3/8/8/8/8/8/8/3QK3 w - - 0 1")?;
+    let black = Position::from_fen("4k3/8/8/8/8/8/8/3QK3 b - - 0 1")?;
+    assert!(evaluate(&white, &weights) > 0);
+    assert_eq!(evaluate(&black, &weights), -evaluate(&white, &weights));
+
+    Ok(())
+}
+
+#[test]
+fn mirrored_positions_score_the_same() -> Result<()> {
+    let weights = EvalWeights::default();
+    let white = Position::from_fen("r3k2r/pp3ppp/2n5/3p4/8/2N2N2/PPP2PPP/R3K2R w KQkq - 0 1")?;
+    let black = Position::from_fen("r3k2r/ppp2ppp/2n2n2/8/3P4/2N5/PP3PPP/R3K2R b KQkq - 0 1")?;
+
+    assert_eq!(evaluate(&white, &weights), evaluate(&black, &weights));
+
+    Ok(())
+}
+
+#[test]
+fn pawn_structure_counts() -> Result<()> {
+    let weights = EvalWeights::default();
+
+    // the d-pawn is passed unless the black pawn is on the file next to it
+    let passed = Position::from_fen("4k3/p7/8/3P4/8/8/8/4K3 w - - 0 1")?;
+    let blocked = Position::from_fen("4k3/4p3/8/3P4/8/8/8/4K3 w - - 0 1")?;
+    assert!(evaluate(&passed, &weights) > evaluate(&blocked, &weights));
+
+    // doubled pawns are worth less than pawns side by side
+    let doubled = Position::from_fen("4k3/8/8/8/3P4/3P4/8/4K3 w - - 0 1")?;
+    let side_by_side = Position::from_fen("4k3/8/8/8/8/3PP3/8/4K3 w - - 0 1")?;
+    assert!(evaluate(&side_by_side, &weights) > evaluate(&doubled, &weights));
+
+    Ok(())
+}
+
+#[test]
+fn weights_are_read_from_ron() -> Result<()> {
+    let defaults = include_str!("../assets/eval.ron");
+    assert_eq!(EvalWeights::from_ron(defaults)?, EvalWeights::default());
+
+    // pawns worth nothing at all
+    let weights = EvalWeights::from_ron(&defaults.replace("pawn: (100, 120)", "pawn: (0, 0)"))?;
+    assert_eq!(weights.material.pawn, Phased(0, 0));
+
+    let short = defaults.replace("passed_pawn: [(0, 0), ", "passed_pawn: [");
+    assert!(matches!(
+        EvalWeights::from_ron(&short),
+        Err(WeightsError::TableSize { len: 7, .. })
+    ));
+    assert!(matches!(
+        EvalWeights::from_ron("(material: ())"),
+        Err(WeightsError::Parse(_))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn finds_mate_in_one() -> Result<()> {
+    let position = Position::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1")?;
+    let result = search(&position, 2);
+
+    assert_eq!(result.best_move, Some(Move::new(sq("a1"), sq("a8"))));
+    assert_eq!(result.score, MATE - 1);
+
+    Ok(())
+}
+
+#[test]
+fn takes_a_hanging_queen() -> Result<()> {
+    let position = Position::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1")?;
+    let result = search(&position, 2);
+
+    assert_eq!(result.best_move, Some(Move::new(sq("d2"), sq("d5"))));
+    assert!(result.score > 0);
+
+    Ok(())
+}
+
+#[test]
+fn mated_and_stalemated_sides_have_no_move() -> Result<()> {
+    let mated = Position::from_fen("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1")?;
+    let result = search(&mated, 3);
+    assert_eq!(result.best_move, None);
+    assert_eq!(result.score, -MATE);
+
+    let stalemated = Position::from_fen("7k/5K2/6Q1/8/8/8/8/8 b - - 0 1")?;
+    let result = search(&stalemated, 3);
+    assert_eq!(result.best_move, None);
+    assert_eq!(result.score, 0);
+
+    Ok(())
+}
+
+#[test]
+fn a_stopped_search_still_has_a_move() {
+    let stop = AtomicBool::new(true);
+    let result = search_with_limits(
+        &Position::default(),
+        SearchLimits::depth(6),
+        &EvalWeights::default(),
+        &mut TranspositionTable::new(1),
+        &stop,
+    );
+
+    assert!(result.best_move.is_some());
+    // it gave up right after the first move
+    assert!(result.stats.nodes <= 1);
+}
+
+#[test]
+fn fixed_depth_searches_every_depth_up_to_it() {
+    let result = search(&Position::default(), 3);
+
+    assert_eq!(result.depth, 3);
+    assert!(result.best_move.is_some());
+}
+
+#[test]
+fn node_limit_keeps_the_last_finished_depth() {
+    let stop = AtomicBool::new(false);
+    let limits = SearchLimits::nodes(5_000);
+    let result = search_with_limits(
+        &Position::default(),
+        limits,
+        &EvalWeights::default(),
+        &mut TranspositionTable::new(1),
+        &stop,
+    );
+
+    // cut short a little after the limit, before getting far
+    assert!(result.stats.nodes <= 5_000);
+    assert!(result.depth >= 1 && result.depth < 6);
+    assert!(result.best_move.is_some());
+
+    // the same limit gives the same move every time
+    let again = search_with_limits(
+        &Position::default(),
+        limits,
+        &EvalWeights::default(),
+        &mut TranspositionTable::new(1),
+        &stop,
+    );
+    assert_eq!(again, result);
+}
+
+#[test]
+fn time_limit_is_kept() {
+    let stop = AtomicBool::new(false);
+    let started = Instant::now();
+    let result = search_with_limits(
+        &Position::default(),
+        SearchLimits::time(Duration::from_millis(200)),
+        &EvalWeights::default(),
+        &mut TranspositionTable::new(1),
+        &stop,
+    );
+
+    assert!(started.elapsed() < Duration::from_secs(1));
+    assert!(result.depth >= 1);
+    assert!(result.best_move.is_some());
+}
+
+#[test]
+fn clock_is_spread_over_the_moves_left() {
+    let minute = Duration::from_secs(60);
+    let second = Duration::from_secs(1);
+
+    let budget = SearchLimits::from_clock(minute, Duration::ZERO, Some(10)).time;
+    assert!(matches!(budget, Some(time) if time > 5 * second && time < 6 * second));
+
+    // most of the increment is spent as well
+    let budget = SearchLimits::from_clock(minute, 4 * second, Some(10)).time;
+    assert!(matches!(budget, Some(time) if time > 8 * second && time < 9 * second));
+
+    // but never more than what's on the clock
+    let budget = SearchLimits::from_clock(second, 10 * second, None).time;
+    assert!(matches!(budget, Some(time) if time < second));
+}
+
+#[test]
+fn table_keeps_the_deeper_entry() {
+    let mut table = TranspositionTable::new(1);
+    let entry = |key, depth| Entry {
+        key,
+        depth,
+        score: 10,
+        bound: Bound::Exact,
+        best_move: None,
+    };
+
+    assert_eq!(table.probe(1), None);
+    table.store(entry(1, 4));
+    assert_eq!(table.probe(1), Some(entry(1, 4)));
+
+    // another position in the same slot doesn't push out a deeper search
+    let slots = 1 << 40;
+    table.store(entry(1 + slots, 2));
+    assert_eq!(table.probe(1), Some(entry(1, 4)));
+    assert_eq!(table.probe(1 + slots), None);
+
+    // but the same position searched again replaces it
+    table.store(entry(1, 2));
+    assert_eq!(table.probe(1), Some(entry(1, 2)));
+
+    table.clear();
+    assert_eq!(table.probe(1), None);
+
+    // a table of size 0 never stores anything
+    let mut table = TranspositionTable::new(0);
+    table.store(entry(1, 4));
+    assert_eq!(table.probe(1), None);
+}
+
+#[test]
+fn table_saves_nodes() -> Result<()> {
+    let position =
+        Position::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")?;
+    let run = |megabytes| {
+        search_with_limits(
+            &position,
+            SearchLimits::depth(4),
+            &EvalWeights::default(),
+            &mut TranspositionTable::new(megabytes),
+            &AtomicBool::new(false),
+        )
+    };
+
+    let with_table = run(16);
+    let without_table = run(0);
+    assert!(with_table.stats.table_hits > 0);
+    assert_eq!(without_table.stats.table_hits, 0);
+    assert!(with_table.stats.nodes < without_table.stats.nodes);
+    // the table only skips work, the answer stays the same
+    assert_eq!(with_table.score, without_table.score);
+
+    // most cutoffs come from the first move tried
+    let stats = with_table.stats;
+    assert!(stats.first_move_cutoffs * 2 > stats.cutoffs);
+
+    Ok(())
+}
+
+#[test]
+fn static_exchange() -> Result<()> {
+    // the d5 pawn is defended by the c6 pawn
+    let position = Position::from_fen("4k3/8/2p5/3p4/4P3/8/8/Q3K3 w - - 0 1")?;
+    assert_eq!(see(&position, Move::new(sq("e4"), sq("d5"))), 0);
+    let position = Position::from_fen("4k3/8/2p5/3p4/8/8/8/3QK3 w - - 0 1")?;
+    assert_eq!(see(&position, Move::new(sq("d1"), sq("d5"))), -800);
+
+    let position = Position::from_fen("4k3/8/8/4p3/8/8/8/4R1K1 w - - 0 1")?;
+    assert_eq!(see(&position, Move::new(sq("e1"), sq("e5"))), 100);
+
+    // the rook behind the first one takes back as well, without it the pawn costs a rook
+    let position = Position::from_fen("4k3/3r4/8/3p4/8/8/3R4/3RK3 w - - 0 1")?;
+    assert_eq!(see(&position, Move::new(sq("d2"), sq("d5"))), 100);
+    let position = Position::from_fen("4k3/3r4/8/3p4/8/8/3R4/4K3 w - - 0 1")?;
+    assert_eq!(see(&position, Move::new(sq("d2"), sq("d5"))), -400);
+
+    // the bishop takes back before the rook does
+    let position = Position::from_fen("3r2k1/8/4b3/3n4/2P5/5B2/8/6K1 w - - 0 1")?;
+    assert_eq!(see(&position, Move::new(sq("f3"), sq("d5"))), 220);
+
+    Ok(())
+}
+
+#[test]
+fn exchanges_are_played_out_at_the_horizon() -> Result<()> {
+    // taking on e5 gives check but loses the queen to the d6 pawn
+    let position = Position::from_fen("4k3/8/3p4/4p3/8/8/4Q3/4K3 w - - 0 1")?;
+    let result = search(&position, 1);
+
+    assert_ne!(result.best_move, Some(Move::new(sq("e2"), sq("e5"))));
+    // a queen against two pawns, not a queen and a pawn
+    assert!(result.score < 900);
+
+    Ok(())
+}
+
+#[test]
+fn polyglot_keys() -> Result<()> {
+    let keys = PolyglotKeys::default();
+    let key = |fen: &str| -> Result<u64> { Ok(keys.key(&Position::from_fen(fen)?)) };
+
+    // the keys published with the Polyglot format
+    let mut position = Position::default();
+    assert_eq!(keys.key(&position), 0x463b_9618_1691_fc9c);
+    let published = [
+        ("e2e4", 0x823c_9b50_fd11_4196),
+        ("d7d5", 0x0756_b944_61c5_0fb0),
+        ("e4e5", 0x662f_afb9_65db_29d4),
+        // f5 can be taken en passant
+        ("f7f5", 0x22a4_8b5a_8e47_ff78),
+        ("e1e2", 0x652a_607c_a3f2_42c1),
+        ("e8f7", 0x00fd_d303_c946_bdd9),
+    ];
+    for (uci, published) in published {
+        position.apply(position.parse_uci(uci)?);
+        assert_eq!(keys.key(&position), published, "after {}", uci);
+    }
+    assert_eq!(
+        key("rnbqkbnr/p1pppppp/8/8/PpP4P/8/1P1PPPP1/RNBQKBNR b KQkq c3 0 3")?,
+        0x3c81_23ea_7b06_7637
+    );
+    assert_eq!(
+        key("rnbqkbnr/p1pppppp/8/8/P6P/R1p5/1P1PPPP1/1NBQKBNR b Kkq - 1 4")?,
+        0x5c3f_9b82_9b27_9560
+    );
+
+    // the same position by two move orders
+    let mut one = Position::default();
+    let mut other = Position::default();
+    for uci in ["g1f3", "g8f6", "b1c3"] {
+        one.apply(one.parse_uci(uci)?);
+    }
+    for uci in ["b1c3", "g8f6", "g1f3"] {
+        other.apply(other.parse_uci(uci)?);
+    }
+    assert_eq!(keys.key(&one), keys.key(&other));
+    assert_ne!(keys.key(&one), keys.key(&Position::default()));
+
+    // the side to move and the castling rights count
+    let fen = "r3k2r/8/8/8/8/8/8/R3K2R";
+    assert_ne!(
+        key(&format!("{} w KQkq - 0 1", fen))?,
+        key(&format!("{} b KQkq - 0 1", fen))?
+    );
+    assert_ne!(
+        key(&format!("{} w KQkq - 0 1", fen))?,
+        key(&format!("{} w Kkq - 0 1", fen))?
+    );
+
+    // an en passant square only when it can be taken
+    let no_taker = "4k3/8/8/8/4P3/8/8/4K3";
+    assert_eq!(
+        key(&format!("{} b - e3 0 1", no_taker))?,
+        key(&format!("{} b - - 0 1", no_taker))?
+    );
+    let taker = "4k3/8/8/8/3pP3/8/8/4K3";
+    assert_ne!(
+        key(&format!("{} b - e3 0 1", taker))?,
+        key(&format!("{} b - - 0 1", taker))?
+    );
+
+    // only Polyglot's own numbers are taken from a file
+    assert!(matches!(
+        PolyglotKeys::from_randoms(vec![1, 2, 3]),
+        Err(BookError::KeyCount(3))
+    ));
+    let path = std::env::temp_dir().join("bevy_chess_test_randoms.txt");
+    let randoms: Vec<String> = (1..=781).map(|i| format!("0x{:016X}ULL", i)).collect();
+    std::fs::write(&path, randoms.join(",\n"))?;
+    assert!(matches!(
+        PolyglotKeys::load(&path),
+        Err(BookError::NotPolyglot)
+    ));
+    std::fs::remove_file(&path)?;
+    // once it's gone, the error is about the numbers and not a book
+    assert!(matches!(PolyglotKeys::load(&path), Err(BookError::Keys(_))));
+
+    Ok(())
+}
+
+#[test]
+fn books_built_from_games() -> Result<()> {
+    let keys = PolyglotKeys::default();
+    let pgn = "[Result \"1-0\"]\n\n1. e4 e5 2. Nf3 Nc6 3. Bc4 Bc5 4. O-O 1-0\n\n\
+               [Result \"1/2-1/2\"]\n\n1. e4 c5 1/2-1/2\n\n\
+               [Result \"0-1\"]\n\n1. d4 d5 0-1\n\n\
+               [Result \"*\"]\n\n1. c4 *\n";
+    let games: Vec<Pgn> = Pgn::parse_all(pgn).into_iter().collect::<Result<_, _>>()?;
+    let book = Book::build(&keys, &games, 8);
+
+    // e4 won once and drew once, d4 lost and the unfinished c4 doesn't count
+    let start = Position::default();
+    let e4 = start.parse_uci("e2e4")?;
+    assert_eq!(book.moves(&keys, &start), vec![(e4, 3)]);
+    assert_eq!(book.choose(&keys, &start, BookSelection::Best), Some(e4));
+    assert_eq!(
+        book.choose(&keys, &start, BookSelection::Weighted),
+        Some(e4)
+    );
+
+    // black's reply that drew, but not the one that lost
+    let mut position = start.clone();
+    position.apply(e4);
+    assert_eq!(
+        book.moves(&keys, &position),
+        vec![(position.parse_uci("c7c5")?, 1)]
+    );
+
+    // castling is stored as the king taking its rook, and comes back as a king move
+    let mut position = start.clone();
+    for uci in ["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "f8c5"] {
+        position.apply(position.parse_uci(uci)?);
+    }
+    assert_eq!(
+        book.choose(&keys, &position, BookSelection::Best),
+        Some(Move::new(sq("e1"), sq("g1")))
+    );
+
+    // the file format round-trips, 16 bytes an entry
+    let bytes = book.to_bytes();
+    assert_eq!(bytes.len(), book.len() * 16);
+    assert_eq!(Book::from_bytes(&bytes)?, book);
+    assert!(matches!(
+        Book::from_bytes(&bytes[1..]),
+        Err(BookError::Size(_))
+    ));
+
+    // out of the book, the search takes over
+    let mut position = start;
+    position.apply(position.parse_uci("a2a3")?);
+    assert!(book.moves(&keys, &position).is_empty());
+    assert_eq!(book.choose(&keys, &position, BookSelection::Weighted), None);
+
+    Ok(())
+}
@@ -0,0 +1,32 @@
+//! A stand-in UCI engine for the tests of playing against other engines: it answers each
+//! `go` with the next of the moves it was started with, whatever the position, and quits
+//! once it's out of moves.
+use std::io::{self, BufRead};
+
+fn main() {
+    let mut moves = std::env::args().skip(1);
+
+    for line in io::stdin().lock().lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+
+        match line.split_whitespace().next() {
+            Some("uci") => {
+                println!("id name scripted");
+                println!("uciok");
+            }
+            Some("isready") => println!("readyok"),
+            Some("go") => match moves.next() {
+                Some(mv) => {
+                    println!("info depth 1 score cp 0");
+                    println!("bestmove {}", mv);
+                }
+                None => return,
+            },
+            Some("quit") => return,
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use anyhow::Result;
+use bevy_chess::rules::{divide, perft, Position, STARTING_FEN};
+
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+const ENDGAME: &str = "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1";
+const PROMOTIONS: &str = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1";
+const TALKCHESS: &str = "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8";
+const MIDDLEGAME: &str = "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10";
+
+// node counts from the chessprogramming wiki, one per depth starting at 1
+fn assert_perft(fen: &str, counts: &[u64]) -> Result<()> {
+    let position = Position::from_fen(fen)?;
+
+    for (depth, expected) in (1..).zip(counts) {
+        assert_eq!(
+            perft(&position, depth),
+            *expected,
+            "perft({}) of {}",
+            depth,
+            fen
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn start_position() -> Result<()> {
+    assert_perft(STARTING_FEN, &[20, 400, 8_902])
+}
+
+#[test]
+fn kiwipete() -> Result<()> {
+    assert_perft(KIWIPETE, &[48, 2_039])
+}
+
+#[test]
+fn endgame_with_en_passant_pins() -> Result<()> {
+    assert_perft(ENDGAME, &[14, 191, 2_812])
+}
+
+#[test]
+fn promotions_and_castling_through_checks() -> Result<()> {
+    assert_perft(PROMOTIONS, &[6, 264, 9_467])
+}
+
+#[test]
+fn talkchess() -> Result<()> {
+    assert_perft(TALKCHESS, &[44, 1_486])
+}
+
+#[test]
+fn middlegame() -> Result<()> {
+    assert_perft(MIDDLEGAME, &[46, 2_079])
+}
+
+#[test]
+fn divide_adds_up_to_perft() {
+    let position = Position::default();
+    let moves = divide(&position, 3);
+
+    assert_eq!(moves.len(), 20);
+    assert_eq!(moves.iter().map(|(_, nodes)| nodes).sum::<u64>(), 8_902);
+}
+
+// the deeper counts take a while without optimizations, run them with `--ignored`
+#[test]
+#[ignore]
+fn deep_counts() -> Result<()> {
+    assert_perft(STARTING_FEN, &[20, 400, 8_902, 197_281])?;
+    assert_perft(KIWIPETE, &[48, 2_039, 97_862])?;
+    assert_perft(ENDGAME, &[14, 191, 2_812, 43_238])?;
+    assert_perft(TALKCHESS, &[44, 1_486, 62_379])?;
+    assert_perft(MIDDLEGAME, &[46, 2_079, 89_890])
+}
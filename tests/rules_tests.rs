@@ -0,0 +1,793 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use bevy_chess::rules::{
+    attack_map, is_attacked, Board, CastleSide, CastlingRights, EndReason, Game, GameResult, Move,
+    Outcome, ParseMoveError, Pgn, PgnError, PgnTags, Piece, PieceKind, Position, SanError, Square,
+    Team, STARTING_FEN,
+};
+
+fn sq(name: &str) -> Square {
+    name.parse().unwrap()
+}
+
+#[test]
+fn start_position_has_twenty_moves() {
+    let position = Position::default();
+
+    assert_eq!(position.legal_moves().len(), 20);
+}
+
+#[test]
+fn pawn_double_step_needs_both_tiles_free() {
+    let mut board = Board::standard();
+    board.set(sq("e3"), Some(Piece::new(PieceKind::Knight, Team::Black)));
+    let position = Position::new(board, Team::White);
+
+    assert!(position.moves_from(sq("e2")).is_empty());
+
+    let mut board = Board::standard();
+    board.set(sq("e4"), Some(Piece::new(PieceKind::Knight, Team::Black)));
+    let position = Position::new(board, Team::White);
+
+    assert_eq!(
+        position.moves_from(sq("e2")),
+        vec![Move::new(sq("e2"), sq("e3"))]
+    );
+}
+
+#[test]
+fn sliders_stop_at_the_first_piece() {
+    let mut board = Board::empty();
+    board.set(sq("a1"), Some(Piece::new(PieceKind::Rock, Team::White)));
+    board.set(sq("a4"), Some(Piece::new(PieceKind::Pawn, Team::Black)));
+    board.set(sq("c1"), Some(Piece::new(PieceKind::Pawn, Team::White)));
+    let position = Position::new(board, Team::White);
+
+    let mut targets: Vec<String> = position
+        .moves_from(sq("a1"))
+        .iter()
+        .map(|mv| mv.to.to_string())
+        .collect();
+    targets.sort();
+
+    assert_eq!(targets, ["a2", "a3", "a4", "b1"]);
+}
+
+#[test]
+fn apply_moves_the_piece_and_passes_the_turn() -> Result<()> {
+    let mut position = Position::default();
+    position.apply(Move::new("e2".parse()?, "e4".parse()?));
+
+    assert_eq!(position.side_to_move(), Team::Black);
+    assert_eq!(position.board().get("e2".parse()?), None);
+    assert_eq!(
+        position.board().get("e4".parse()?),
+        Some(Piece::new(PieceKind::Pawn, Team::White))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn apply_returns_the_captured_piece() {
+    let mut board = Board::empty();
+    board.set(sq("d4"), Some(Piece::new(PieceKind::Knight, Team::White)));
+    board.set(sq("e6"), Some(Piece::new(PieceKind::Bishop, Team::Black)));
+    let mut position = Position::new(board, Team::White);
+
+    let captured = position.apply(Move::new(sq("d4"), sq("e6")));
+
+    assert_eq!(captured, Some(Piece::new(PieceKind::Bishop, Team::Black)));
+}
+
+#[test]
+fn squares_round_trip_through_their_names() {
+    for square in Square::all() {
+        assert_eq!(square.to_string().parse::<Square>().unwrap(), square);
+    }
+
+    assert!("i1".parse::<Square>().is_err());
+}
+
+#[test]
+fn only_the_side_to_move_has_legal_moves() {
+    let mut position = Position::default();
+    position.apply(Move::new(sq("e2"), sq("e4")));
+
+    let moves = position.legal_moves();
+
+    assert_eq!(moves.len(), 20);
+    assert!(moves
+        .iter()
+        .all(|mv| position.board().get(mv.from).unwrap().team == Team::Black));
+}
+
+#[test]
+fn pinned_pieces_cannot_move_off_the_pin() {
+    let mut board = Board::empty();
+    board.set(sq("e1"), Some(Piece::new(PieceKind::King, Team::White)));
+    board.set(sq("e2"), Some(Piece::new(PieceKind::Knight, Team::White)));
+    board.set(sq("e8"), Some(Piece::new(PieceKind::Rock, Team::Black)));
+    board.set(sq("a8"), Some(Piece::new(PieceKind::King, Team::Black)));
+    let position = Position::new(board, Team::White);
+
+    assert!(position.moves_from(sq("e2")).is_empty());
+}
+
+#[test]
+fn king_cannot_step_into_an_attacked_square() {
+    let mut board = Board::empty();
+    board.set(sq("e1"), Some(Piece::new(PieceKind::King, Team::White)));
+    board.set(sq("d8"), Some(Piece::new(PieceKind::Rock, Team::Black)));
+    board.set(sq("f3"), Some(Piece::new(PieceKind::Pawn, Team::Black)));
+    board.set(sq("a8"), Some(Piece::new(PieceKind::King, Team::Black)));
+    let position = Position::new(board, Team::White);
+
+    let mut targets: Vec<String> = position
+        .moves_from(sq("e1"))
+        .iter()
+        .map(|mv| mv.to.to_string())
+        .collect();
+    targets.sort();
+
+    // d1/d2 are on the rook's file and e2/g2 are covered by the pawn
+    assert_eq!(targets, ["f1", "f2"]);
+}
+
+#[test]
+fn a_check_has_to_be_answered() {
+    let mut board = Board::empty();
+    board.set(sq("e1"), Some(Piece::new(PieceKind::King, Team::White)));
+    board.set(sq("a2"), Some(Piece::new(PieceKind::Rock, Team::White)));
+    board.set(sq("e8"), Some(Piece::new(PieceKind::Rock, Team::Black)));
+    board.set(sq("a8"), Some(Piece::new(PieceKind::King, Team::Black)));
+    let position = Position::new(board, Team::White);
+
+    assert!(position.in_check());
+    assert!(position
+        .legal_moves()
+        .iter()
+        .all(|mv| mv.from == sq("e1") || mv.to == sq("e2")));
+    assert!(is_attacked(position.board(), sq("e4"), Team::Black));
+    assert_eq!(
+        attack_map(position.board(), Team::Black) & (1 << sq("e1").index()),
+        1 << sq("e1").index()
+    );
+}
+
+#[test]
+fn fools_mate_is_checkmate() {
+    let mut position = Position::default();
+    for (from, to) in [("f2", "f3"), ("e7", "e5"), ("g2", "g4"), ("d8", "h4")] {
+        assert_eq!(position.outcome(), None);
+        position.apply(Move::new(sq(from), sq(to)));
+    }
+
+    assert_eq!(
+        position.outcome(),
+        Some(Outcome {
+            result: GameResult::BlackWins,
+            reason: EndReason::Checkmate,
+        })
+    );
+}
+
+#[test]
+fn no_moves_without_check_is_stalemate() {
+    let mut board = Board::empty();
+    board.set(sq("h8"), Some(Piece::new(PieceKind::King, Team::Black)));
+    board.set(sq("f7"), Some(Piece::new(PieceKind::King, Team::White)));
+    board.set(sq("g6"), Some(Piece::new(PieceKind::Queen, Team::White)));
+    let position = Position::new(board, Team::Black);
+
+    assert_eq!(
+        position.outcome(),
+        Some(Outcome {
+            result: GameResult::Draw,
+            reason: EndReason::Stalemate,
+        })
+    );
+}
+
+fn castling_position() -> Position {
+    let mut board = Board::empty();
+    board.set(sq("e1"), Some(Piece::new(PieceKind::King, Team::White)));
+    board.set(sq("a1"), Some(Piece::new(PieceKind::Rock, Team::White)));
+    board.set(sq("h1"), Some(Piece::new(PieceKind::Rock, Team::White)));
+    board.set(sq("e8"), Some(Piece::new(PieceKind::King, Team::Black)));
+    let mut position = Position::new(board, Team::White);
+    position.set_castling_rights(CastlingRights::all());
+    position
+}
+
+#[test]
+fn king_castles_to_both_sides() {
+    let mut position = castling_position();
+    let moves = position.moves_from(sq("e1"));
+
+    assert!(moves.contains(&Move::new(sq("e1"), sq("g1"))));
+    assert!(moves.contains(&Move::new(sq("e1"), sq("c1"))));
+
+    let castle = Move::new(sq("e1"), sq("c1"));
+    assert_eq!(position.castle_side(castle), Some(CastleSide::Queen));
+    assert_eq!(
+        position.castling_rook(castle),
+        Some(Move::new(sq("a1"), sq("d1")))
+    );
+
+    position.apply(castle);
+
+    assert_eq!(
+        position.board().get(sq("d1")),
+        Some(Piece::new(PieceKind::Rock, Team::White))
+    );
+    assert_eq!(position.board().get(sq("a1")), None);
+    assert!(!position
+        .castling_rights()
+        .has(Team::White, CastleSide::King));
+}
+
+#[test]
+fn castling_is_not_allowed_out_of_or_through_check() {
+    let mut position = castling_position();
+    let mut board = position.board().clone();
+    // attacks f1, the tile the king crosses when castling king side
+    board.set(sq("f8"), Some(Piece::new(PieceKind::Rock, Team::Black)));
+    let rights = position.castling_rights();
+    position = Position::new(board, Team::White);
+    position.set_castling_rights(rights);
+
+    let moves = position.moves_from(sq("e1"));
+    assert!(!moves.contains(&Move::new(sq("e1"), sq("g1"))));
+    assert!(moves.contains(&Move::new(sq("e1"), sq("c1"))));
+
+    let mut board = position.board().clone();
+    board.set(sq("f8"), None);
+    board.set(sq("e5"), Some(Piece::new(PieceKind::Rock, Team::Black)));
+    position = Position::new(board, Team::White);
+    position.set_castling_rights(rights);
+
+    assert!(position
+        .moves_from(sq("e1"))
+        .iter()
+        .all(|mv| position.castle_side(*mv).is_none()));
+}
+
+#[test]
+fn moving_a_rook_loses_its_castling_right() {
+    let mut position = castling_position();
+    position.apply(Move::new(sq("h1"), sq("h2")));
+    position.apply(Move::new(sq("e8"), sq("d8")));
+    position.apply(Move::new(sq("h2"), sq("h1")));
+    position.apply(Move::new(sq("d8"), sq("e8")));
+
+    let moves = position.moves_from(sq("e1"));
+    assert!(!moves.contains(&Move::new(sq("e1"), sq("g1"))));
+    assert!(moves.contains(&Move::new(sq("e1"), sq("c1"))));
+
+    // a blocked path forbids it too
+    let mut position = castling_position();
+    let mut board = position.board().clone();
+    board.set(sq("b1"), Some(Piece::new(PieceKind::Knight, Team::White)));
+    let rights = position.castling_rights();
+    position = Position::new(board, Team::White);
+    position.set_castling_rights(rights);
+
+    assert!(!position
+        .moves_from(sq("e1"))
+        .contains(&Move::new(sq("e1"), sq("c1"))));
+}
+
+#[test]
+fn pawn_captures_en_passant_right_after_the_double_step() {
+    let mut position = Position::default();
+    for (from, to) in [("e2", "e4"), ("a7", "a6"), ("e4", "e5"), ("d7", "d5")] {
+        position.apply(Move::new(sq(from), sq(to)));
+    }
+
+    assert_eq!(position.en_passant(), Some(sq("d6")));
+
+    let capture = Move::new(sq("e5"), sq("d6"));
+    assert!(position.moves_from(sq("e5")).contains(&capture));
+    assert_eq!(position.en_passant_capture(capture), Some(sq("d5")));
+
+    let captured = position.clone().apply(capture);
+    assert_eq!(captured, Some(Piece::new(PieceKind::Pawn, Team::Black)));
+
+    let mut after = position.clone();
+    after.apply(capture);
+    assert_eq!(after.board().get(sq("d5")), None);
+
+    // the chance is gone once another move is played
+    position.apply(Move::new(sq("b1"), sq("c3")));
+    position.apply(Move::new(sq("a6"), sq("a5")));
+    assert!(!position.moves_from(sq("e5")).contains(&capture));
+}
+
+#[test]
+fn pawns_promote_on_the_last_rank() {
+    let mut board = Board::empty();
+    board.set(sq("e1"), Some(Piece::new(PieceKind::King, Team::White)));
+    board.set(sq("a8"), Some(Piece::new(PieceKind::King, Team::Black)));
+    board.set(sq("g7"), Some(Piece::new(PieceKind::Pawn, Team::White)));
+    board.set(sq("h8"), Some(Piece::new(PieceKind::Rock, Team::Black)));
+    let mut position = Position::new(board, Team::White);
+
+    let moves = position.moves_from(sq("g7"));
+
+    // a push and a capture, each with the four possible promotions
+    assert_eq!(moves.len(), 8);
+    assert!(moves.iter().all(|mv| mv.promotion.is_some()));
+
+    let promotion = Move::with_promotion(sq("g7"), sq("h8"), PieceKind::Knight);
+    assert_eq!(promotion.to_string(), "g7h8n");
+
+    position.apply(promotion);
+    assert_eq!(
+        position.board().get(sq("h8")),
+        Some(Piece::new(PieceKind::Knight, Team::White))
+    );
+}
+
+#[test]
+fn shuffling_knights_draws_by_threefold_repetition() {
+    let mut game = Game::default();
+    let shuffle = [("g1", "f3"), ("g8", "f6"), ("f3", "g1"), ("f6", "g8")];
+
+    for (from, to) in shuffle.iter().chain(shuffle.iter()) {
+        assert_eq!(game.outcome(), None);
+        game.play(Move::new(sq(from), sq(to)));
+    }
+
+    assert_eq!(game.repetitions(), 3);
+    assert_eq!(
+        game.outcome(),
+        Some(Outcome {
+            result: GameResult::Draw,
+            reason: EndReason::ThreefoldRepetition,
+        })
+    );
+}
+
+#[test]
+fn fifty_moves_without_captures_or_pawn_moves_is_a_draw() {
+    let mut board = Board::empty();
+    board.set(sq("a1"), Some(Piece::new(PieceKind::King, Team::White)));
+    board.set(sq("h1"), Some(Piece::new(PieceKind::Rock, Team::White)));
+    board.set(sq("a8"), Some(Piece::new(PieceKind::King, Team::Black)));
+    let mut position = Position::new(board, Team::White);
+    position.set_halfmove_clock(99);
+
+    assert_eq!(position.outcome(), None);
+
+    position.apply(Move::new(sq("h1"), sq("h2")));
+
+    assert_eq!(
+        position.outcome().map(|outcome| outcome.reason),
+        Some(EndReason::FiftyMoveRule)
+    );
+
+    // a pawn move starts the count again
+    let mut position = Position::default();
+    position.set_halfmove_clock(40);
+    position.apply(Move::new(sq("e2"), sq("e4")));
+    assert_eq!(position.halfmove_clock(), 0);
+}
+
+#[test]
+fn bare_kings_and_minor_pieces_are_insufficient_material() {
+    let with = |extra: &[(&str, PieceKind, Team)]| {
+        let mut board = Board::empty();
+        board.set(sq("e1"), Some(Piece::new(PieceKind::King, Team::White)));
+        board.set(sq("e8"), Some(Piece::new(PieceKind::King, Team::Black)));
+        for (square, kind, team) in extra {
+            board.set(sq(square), Some(Piece::new(*kind, *team)));
+        }
+        Position::new(board, Team::White)
+    };
+
+    assert!(with(&[]).has_insufficient_material());
+    assert!(with(&[("c3", PieceKind::Knight, Team::White)]).has_insufficient_material());
+    // both bishops on dark squares
+    assert!(with(&[
+        ("c1", PieceKind::Bishop, Team::White),
+        ("f8", PieceKind::Bishop, Team::Black)
+    ])
+    .has_insufficient_material());
+    assert!(!with(&[
+        ("c1", PieceKind::Bishop, Team::White),
+        ("c8", PieceKind::Bishop, Team::Black)
+    ])
+    .has_insufficient_material());
+    assert!(!with(&[("a2", PieceKind::Pawn, Team::White)]).has_insufficient_material());
+    assert_eq!(
+        with(&[]).outcome().map(|outcome| outcome.reason),
+        Some(EndReason::InsufficientMaterial)
+    );
+}
+
+#[test]
+fn starting_position_round_trips_through_fen() -> Result<()> {
+    assert_eq!(Position::default().to_fen(), STARTING_FEN);
+    assert_eq!(Position::from_fen(STARTING_FEN)?, Position::default());
+
+    Ok(())
+}
+
+#[test]
+fn fen_tracks_en_passant_and_move_counters() -> Result<()> {
+    let mut position = Position::default();
+    position.apply(Move::new(sq("e2"), sq("e4")));
+    assert_eq!(
+        position.to_fen(),
+        "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+    );
+
+    position.apply(Move::new(sq("g8"), sq("f6")));
+    assert_eq!(
+        position.to_fen(),
+        "rnbqkb1r/pppppppp/5n2/8/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 1 2"
+    );
+
+    let fen = "r3k2r/8/8/3pP3/8/8/8/R3K2R w Kq d6 3 20";
+    let position: Position = fen.parse()?;
+    assert_eq!(position.to_fen(), fen);
+    assert!(position
+        .legal_moves()
+        .contains(&Move::new(sq("e5"), sq("d6"))));
+    assert!(position
+        .legal_moves()
+        .contains(&Move::new(sq("e1"), sq("g1"))));
+    assert!(!position
+        .legal_moves()
+        .contains(&Move::new(sq("e1"), sq("c1"))));
+
+    Ok(())
+}
+
+#[test]
+fn malformed_fen_is_rejected() {
+    let invalid = [
+        "",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnbqkbnr/ppppXppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnbq1bnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQ - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQxq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e4 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - zero 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 extra",
+    ];
+
+    for fen in invalid {
+        assert!(Position::from_fen(fen).is_err(), "{:?} was accepted", fen);
+    }
+
+    // the move counters are optional
+    assert_eq!(
+        Position::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -"),
+        Ok(Position::default())
+    );
+}
+
+fn san_after(fen: &str, mv: Move) -> Result<String> {
+    Ok(Position::from_fen(fen)?.san(mv))
+}
+
+#[test]
+fn moves_are_written_in_standard_algebraic_notation() -> Result<()> {
+    let start = Position::default();
+    assert_eq!(start.san(Move::new(sq("e2"), sq("e4"))), "e4");
+    assert_eq!(start.san(Move::new(sq("g1"), sq("f3"))), "Nf3");
+
+    // knights on b8 and f6 can both reach d7, rooks on a1 and a5 both reach a3
+    let fen = "r3k2r/8/5n2/R7/8/8/8/R3K1n1 b kq - 0 1";
+    assert_eq!(san_after(fen, Move::new(sq("e8"), sq("c8")))?, "O-O-O");
+    let fen = "1n2k3/8/5n2/R7/8/8/8/R3K3 w - - 0 1";
+    assert_eq!(san_after(fen, Move::new(sq("a1"), sq("a3")))?, "R1a3");
+    let fen = "1n2k3/8/5n2/R7/8/8/8/R3K3 b - - 0 1";
+    assert_eq!(san_after(fen, Move::new(sq("b8"), sq("d7")))?, "Nbd7");
+
+    let fen = "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1";
+    assert_eq!(san_after(fen, Move::new(sq("e5"), sq("d6")))?, "exd6");
+
+    let fen = "3k4/4P3/8/8/8/8/8/4K3 w - - 0 1";
+    assert_eq!(
+        san_after(
+            fen,
+            Move::with_promotion(sq("e7"), sq("e8"), PieceKind::Queen)
+        )?,
+        "e8=Q+"
+    );
+
+    let mut position = Position::default();
+    for (from, to) in [("f2", "f3"), ("e7", "e5"), ("g2", "g4")] {
+        position.apply(Move::new(sq(from), sq(to)));
+    }
+    assert_eq!(position.san(Move::new(sq("d8"), sq("h4"))), "Qh4#");
+
+    Ok(())
+}
+
+#[test]
+fn games_are_exported_as_pgn() {
+    let mut game = Game::default();
+    for (from, to) in [("f2", "f3"), ("e7", "e5"), ("g2", "g4"), ("d8", "h4")] {
+        game.play(Move::new(sq(from), sq(to)));
+    }
+
+    assert_eq!(game.san_moves(), ["f3", "e5", "g4", "Qh4#"]);
+
+    let tags = PgnTags {
+        event: "Test".to_string(),
+        white: "Fool".to_string(),
+        ..Default::default()
+    };
+    let mut pgn = Pgn::from_game(&game, tags);
+    assert_eq!(
+        pgn.to_string(),
+        "[Event \"Test\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n[Round \"?\"]\n\
+         [White \"Fool\"]\n[Black \"?\"]\n[Result \"0-1\"]\n\n1. f3 e5 2. g4 Qh4# 0-1\n"
+    );
+
+    pgn.clocks = vec![Duration::from_secs(3), Duration::from_secs(75)];
+    assert!(pgn
+        .to_string()
+        .ends_with("1. f3 {[%emt 0:00:03]} 1... e5 {[%emt 0:01:15]} 2. g4 Qh4# 0-1\n"));
+}
+
+#[test]
+fn unfinished_games_from_a_fen_keep_their_setup() -> Result<()> {
+    let fen = "4k3/8/8/8/8/8/4P3/4K3 b - - 0 30";
+    let mut game = Game::new(Position::from_fen(fen)?);
+    game.play(Move::new(sq("e8"), sq("d7")));
+    game.play(Move::new(sq("e2"), sq("e4")));
+
+    let pgn = Pgn::from_game(&game, PgnTags::default()).to_string();
+    assert!(
+        pgn.contains("[Result \"*\"]\n[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 30\"]\n")
+    );
+    assert!(pgn.ends_with("\n30... Kd7 31. e4 *\n"));
+
+    Ok(())
+}
+
+#[test]
+fn collections_are_read_game_by_game() {
+    let pgn = "[Event \"a\"]\n\n1. e4 e5 2. Nf3 1-0\n\n[Event \"b\"]\n\n1. e5 *\n\n\
+               [Event \"c\"]\n[Result \"1/2-1/2\"]\n\n1. d4 d5\n";
+    let games = Pgn::parse_all(pgn);
+
+    assert_eq!(games.len(), 3);
+    let first = games[0].as_ref().unwrap();
+    assert_eq!(first.tags.event, "a");
+    assert_eq!(first.moves.len(), 3);
+    assert!(matches!(games[1], Err(PgnError::Move { ply: 1, .. })));
+    let last = games[2].as_ref().unwrap();
+    assert_eq!(last.result, Some(GameResult::Draw));
+    assert_eq!(last.moves.len(), 2);
+}
+
+#[test]
+fn san_is_resolved_against_the_position() -> Result<()> {
+    let start = Position::default();
+    assert_eq!(start.parse_san("e4")?, Move::new(sq("e2"), sq("e4")));
+    assert_eq!(start.parse_san("Nf3!?")?, Move::new(sq("g1"), sq("f3")));
+    assert!(matches!(start.parse_san("e5"), Err(SanError::Illegal(_))));
+    assert!(matches!(start.parse_san("Zz9"), Err(SanError::Invalid(_))));
+
+    let position = Position::from_fen("1n2k3/8/5n2/R7/8/8/8/R3K3 b - - 0 1")?;
+    assert_eq!(position.parse_san("Nbd7")?, Move::new(sq("b8"), sq("d7")));
+    assert!(matches!(
+        position.parse_san("Nd7"),
+        Err(SanError::Ambiguous(_))
+    ));
+    let position = Position::from_fen("1n2k3/8/5n2/R7/8/8/8/R3K3 w - - 0 1")?;
+    assert_eq!(position.parse_san("R1a3")?, Move::new(sq("a1"), sq("a3")));
+
+    let position = Position::from_fen("r3k2r/8/8/8/8/8/8/4K3 b kq - 0 1")?;
+    assert_eq!(position.parse_san("O-O-O")?, Move::new(sq("e8"), sq("c8")));
+    assert_eq!(position.parse_san("0-0")?, Move::new(sq("e8"), sq("g8")));
+
+    let position = Position::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1")?;
+    assert_eq!(
+        position.parse_san("exd6 e.p.")?,
+        Move::new(sq("e5"), sq("d6"))
+    );
+
+    let position = Position::from_fen("3k4/4P3/8/8/8/8/8/4K3 w - - 0 1")?;
+    assert_eq!(
+        position.parse_san("e8=N")?,
+        Move::with_promotion(sq("e7"), sq("e8"), PieceKind::Knight)
+    );
+    assert!(matches!(
+        position.parse_san("e8"),
+        Err(SanError::Illegal(_))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn pgn_games_are_read_back() -> Result<()> {
+    let text = r#"[Event "Casual game"]
+[Site "?"]
+[Date "2023.01.15"]
+[Round "-"]
+[White "Alice \"A\""]
+[Black "Bob"]
+[Result "1-0"]
+
+1. e4 {best by test} e5 2. Bc4 (2. Nf3 Nc6 (2... d6) 3. Bb5) 2... Nc6 $1
+3.Qh5 Nf6?? ; a blunder
+4. Qxf7# 1-0
+"#;
+    let pgn: Pgn = text.parse()?;
+
+    assert_eq!(pgn.tags.white, "Alice \"A\"");
+    assert_eq!(pgn.tags.date, "2023.01.15");
+    assert_eq!(pgn.result, Some(GameResult::WhiteWins));
+    assert_eq!(pgn.moves.len(), 7);
+    assert_eq!(pgn.moves[6], Move::new(sq("h5"), sq("f7")));
+
+    // writing it again gives the same moves and tags
+    assert_eq!(Pgn::parse(&pgn.to_string())?, pgn);
+
+    let illegal = "1. e4 e5 2. Ke3 *";
+    assert!(matches!(
+        Pgn::parse(illegal),
+        Err(PgnError::Move { ply: 3, .. })
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn pgn_games_can_start_from_a_fen() -> Result<()> {
+    let mut game = Game::new(Position::from_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 0 30")?);
+    game.play(Move::new(sq("e8"), sq("d7")));
+    game.play(Move::new(sq("e2"), sq("e4")));
+
+    let pgn = Pgn::parse(&Pgn::from_game(&game, PgnTags::default()).to_string())?;
+    assert_eq!(&pgn.start, game.start());
+    assert_eq!(pgn.moves, game.moves());
+    assert_eq!(pgn.result, None);
+
+    Ok(())
+}
+
+#[test]
+fn moves_round_trip_through_long_algebraic_notation() -> Result<()> {
+    for uci in ["e2e4", "g7h8n", "e1g1"] {
+        assert_eq!(uci.parse::<Move>()?.to_string(), uci);
+    }
+    assert_eq!(
+        "a2a1Q".parse::<Move>()?,
+        Move::with_promotion(sq("a2"), sq("a1"), PieceKind::Queen)
+    );
+    for invalid in ["", "e2", "e2e9", "e7e8k", "e2e4qq"] {
+        assert!(matches!(
+            invalid.parse::<Move>(),
+            Err(ParseMoveError::Invalid(_))
+        ));
+    }
+
+    let start = Position::default();
+    assert_eq!(start.parse_uci("g1f3")?, Move::new(sq("g1"), sq("f3")));
+    assert!(matches!(
+        start.parse_uci("e2e5"),
+        Err(ParseMoveError::Illegal(_))
+    ));
+
+    let position = Position::from_fen("3k4/4P3/8/8/8/8/8/4K3 w - - 0 1")?;
+    assert!(position.parse_uci("e7e8").is_err());
+    let mv = position.parse_uci("e7e8q")?;
+    assert_eq!(position.san(mv), "e8=Q+");
+
+    Ok(())
+}
+
+#[test]
+fn board_bitboards_follow_the_pieces() {
+    let bits = |names: &[&str]| {
+        names
+            .iter()
+            .fold(0u64, |bits, name| bits | 1 << sq(name).index())
+    };
+    let mut board = Board::empty();
+    board.set(sq("d4"), Some(Piece::new(PieceKind::Rock, Team::White)));
+    board.set(sq("d6"), Some(Piece::new(PieceKind::Pawn, Team::Black)));
+    board.set(sq("f4"), Some(Piece::new(PieceKind::Knight, Team::White)));
+
+    assert_eq!(board.occupied(), bits(&["d4", "d6", "f4"]));
+    assert_eq!(board.team(Team::Black), bits(&["d6"]));
+    assert_eq!(board.pieces_of(PieceKind::Rock, Team::White), bits(&["d4"]));
+
+    // the rook stops on the pawn and next to the knight, the knight jumps over everything
+    let rook = bits(&["d1", "d2", "d3", "d5", "d6", "a4", "b4", "c4", "e4", "f4"]);
+    let knight = bits(&["e6", "g6", "h5", "h3", "g2", "e2", "d5", "d3"]);
+    assert_eq!(attack_map(&board, Team::White), rook | knight);
+
+    // replacing and removing pieces clears their old bits
+    board.set(sq("d4"), Some(Piece::new(PieceKind::Bishop, Team::Black)));
+    board.set(sq("f4"), None);
+    assert_eq!(board.team(Team::White), 0);
+    assert_eq!(board.pieces_of(PieceKind::Rock, Team::White), 0);
+    assert_eq!(board.team(Team::Black), bits(&["d4", "d6"]));
+}
+
+#[test]
+fn zobrist_keys_are_kept_up_to_date_by_moves() -> Result<()> {
+    // every position two plies deep, with castling, en passant and promotions among them,
+    // has the key it would have if it was set up from scratch
+    for fen in [
+        STARTING_FEN,
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "4k3/8/8/8/2p5/8/1P1P4/4K3 w - - 0 1",
+    ] {
+        let position = Position::from_fen(fen)?;
+        for mv in position.legal_moves() {
+            let mut after = position.clone();
+            after.apply(mv);
+            assert_eq!(
+                after.zobrist(),
+                Position::from_fen(&after.to_fen())?.zobrist()
+            );
+
+            for reply in after.legal_moves() {
+                let mut next = after.clone();
+                next.apply(reply);
+                assert_eq!(
+                    next.zobrist(),
+                    Position::from_fen(&next.to_fen())?.zobrist()
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn zobrist_keys_tell_positions_apart() -> Result<()> {
+    let key = |fen: &str| -> Result<u64> { Ok(Position::from_fen(fen)?.zobrist()) };
+
+    // the same position reached by different move orders
+    let mut knights_first = Position::default();
+    let mut knights_later = Position::default();
+    for (from, to) in [("g1", "f3"), ("g8", "f6"), ("b1", "c3")] {
+        knights_first.apply(Move::new(sq(from), sq(to)));
+    }
+    for (from, to) in [("b1", "c3"), ("g8", "f6"), ("g1", "f3")] {
+        knights_later.apply(Move::new(sq(from), sq(to)));
+    }
+    assert_eq!(knights_first.zobrist(), knights_later.zobrist());
+
+    assert_ne!(
+        key("4k3/8/8/8/8/8/8/4K3 w - - 0 1")?,
+        key("4k3/8/8/8/8/8/8/4K3 b - - 0 1")?
+    );
+    assert_ne!(
+        key("r3k3/8/8/8/8/8/8/4K3 b q - 0 1")?,
+        key("r3k3/8/8/8/8/8/8/4K3 b - - 0 1")?
+    );
+    // an en passant square only counts when a pawn can take on it
+    assert_ne!(
+        key("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1")?,
+        key("4k3/8/8/3pP3/8/8/8/4K3 w - - 0 1")?
+    );
+    assert_eq!(
+        key("4k3/8/8/3p4/8/8/8/4K3 w - d6 0 1")?,
+        key("4k3/8/8/3p4/8/8/8/4K3 w - - 0 1")?
+    );
+    // the move counters don't
+    assert_eq!(
+        key("4k3/8/8/8/8/8/8/4K3 w - - 0 1")?,
+        key("4k3/8/8/8/8/8/8/4K3 w - - 12 40")?
+    );
+
+    Ok(())
+}
@@ -0,0 +1,307 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    process::{Command as Process, Stdio},
+    time::Duration,
+};
+
+use anyhow::Result;
+use bevy_chess::{
+    engine::{SearchLimits, MATE},
+    rules::{Position, Team},
+    uci::{self, Command, EngineError, EngineProcess, Go, UciError},
+};
+
+// answers every `go` with the next of the moves it's given, then quits
+fn scripted_engine(moves: &[&str]) -> Result<EngineProcess, EngineError> {
+    let moves: Vec<String> = moves.iter().map(|mv| mv.to_string()).collect();
+    EngineProcess::spawn(env!("CARGO_BIN_EXE_scripted-engine"), &moves)
+}
+
+#[test]
+fn simple_commands() {
+    assert_eq!("uci".parse(), Ok(Command::Uci));
+    assert_eq!("  isready ".parse(), Ok(Command::IsReady));
+    assert_eq!("stop".parse(), Ok(Command::Stop));
+    assert_eq!(
+        "joho".parse::<Command>(),
+        Err(UciError::Unknown("joho".to_string()))
+    );
+}
+
+#[test]
+fn position_plays_the_moves() -> Result<()> {
+    let expected =
+        Position::from_fen("rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2")?;
+
+    assert_eq!(
+        "position startpos moves e2e4 e7e5".parse(),
+        Ok(Command::Position(Box::new(expected.clone())))
+    );
+    assert_eq!(
+        "position fen rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1 moves e7e5"
+            .parse(),
+        Ok(Command::Position(Box::new(expected)))
+    );
+    assert_eq!(
+        "position startpos".parse(),
+        Ok(Command::Position(Box::default()))
+    );
+    assert!(matches!(
+        "position startpos moves e2e5".parse::<Command>(),
+        Err(UciError::Move(_))
+    ));
+    assert!(matches!(
+        "position fen 8/8/8 w - - 0 1".parse::<Command>(),
+        Err(UciError::Fen(_))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn go_takes_the_side_to_moves_clock() {
+    let go = match "go wtime 60000 btime 1000 winc 1000 binc 0 movestogo 10".parse() {
+        Ok(Command::Go(go)) => go,
+        other => panic!("{:?}", other),
+    };
+    assert_eq!(go.white_time, Some(Duration::from_secs(60)));
+    assert_eq!(go.moves_to_go, Some(10));
+
+    let white = go.limits(Team::White).time.unwrap();
+    let black = go.limits(Team::Black).time.unwrap();
+    assert!(white > black);
+    assert!(black < Duration::from_millis(100));
+
+    let fixed = Go {
+        depth: Some(4),
+        nodes: Some(1000),
+        ..Go::default()
+    };
+    assert_eq!(
+        fixed.limits(Team::White),
+        SearchLimits {
+            depth: Some(4),
+            nodes: Some(1000),
+            time: None,
+        }
+    );
+    assert_eq!(
+        "go movetime 500"
+            .parse::<Command>()
+            .map(|command| match command {
+                Command::Go(go) => go.limits(Team::Black),
+                _ => SearchLimits::default(),
+            }),
+        Ok(SearchLimits::time(Duration::from_millis(500)))
+    );
+    assert!(matches!(
+        "go depth x".parse::<Command>(),
+        Err(UciError::Value { .. })
+    ));
+}
+
+#[test]
+fn set_option_names_and_values_can_have_spaces() {
+    assert_eq!(
+        "setoption name Eval File value /tmp/my weights.ron".parse(),
+        Ok(Command::SetOption {
+            name: "Eval File".to_string(),
+            value: Some("/tmp/my weights.ron".to_string()),
+        })
+    );
+    assert_eq!(
+        "setoption name Clear Hash".parse(),
+        Ok(Command::SetOption {
+            name: "Clear Hash".to_string(),
+            value: None,
+        })
+    );
+}
+
+#[test]
+fn scores_in_centipawns_or_moves_to_mate() {
+    assert_eq!(uci::score(35), "cp 35");
+    assert_eq!(uci::score(MATE - 1), "mate 1");
+    assert_eq!(uci::score(MATE - 3), "mate 2");
+    assert_eq!(uci::score(-MATE + 2), "mate -1");
+}
+
+#[test]
+fn engine_answers_over_stdin() -> Result<()> {
+    let mut engine = Process::new(env!("CARGO_BIN_EXE_uci"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+    let mut stdin = engine.stdin.take().unwrap();
+    let mut stdout = BufReader::new(engine.stdout.take().unwrap());
+    let mut read_until = |prefix: &str| -> Result<Vec<String>> {
+        let mut lines = Vec::new();
+        loop {
+            let mut line = String::new();
+            if stdout.read_line(&mut line)? == 0 {
+                anyhow::bail!("the engine quit before {:?}", prefix);
+            }
+            let line = line.trim().to_string();
+            let done = line.starts_with(prefix);
+            lines.push(line);
+            if done {
+                return Ok(lines);
+            }
+        }
+    };
+
+    writeln!(stdin, "uci")?;
+    let lines = read_until("uciok")?;
+    assert!(lines[0].starts_with("id name"));
+
+    writeln!(stdin, "isready")?;
+    read_until("readyok")?;
+
+    // mate in one with the rook
+    writeln!(stdin, "position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1")?;
+    writeln!(stdin, "go depth 3")?;
+    let lines = read_until("bestmove")?;
+    assert!(lines.iter().any(|line| line.contains("score mate 1")));
+    assert_eq!(lines.last().unwrap(), "bestmove a1a8");
+
+    // an infinite search waits for stop
+    writeln!(stdin, "position startpos moves e2e4")?;
+    writeln!(stdin, "go infinite")?;
+    writeln!(stdin, "stop")?;
+    let lines = read_until("bestmove")?;
+    assert!(lines.last().unwrap().len() > "bestmove ".len());
+
+    writeln!(stdin, "quit")?;
+    assert!(engine.wait()?.success());
+
+    Ok(())
+}
+
+#[test]
+fn go_and_position_commands_for_other_engines() -> Result<()> {
+    let go = Go {
+        white_time: Some(Duration::from_secs(60)),
+        black_time: Some(Duration::from_millis(59_500)),
+        moves_to_go: Some(20),
+        ..Go::default()
+    };
+    assert_eq!(go.to_string(), "go wtime 60000 btime 59500 movestogo 20");
+    assert_eq!(
+        format!(
+            "{}",
+            Go {
+                infinite: true,
+                ..Go::default()
+            }
+        ),
+        "go infinite"
+    );
+    assert_eq!(
+        "go movetime 250 depth 6".parse(),
+        Ok(Command::Go(Go {
+            move_time: Some(Duration::from_millis(250)),
+            depth: Some(6),
+            ..Go::default()
+        }))
+    );
+
+    let start = Position::default();
+    let e4 = start.parse_uci("e2e4")?;
+    assert_eq!(uci::position_command(&start, &[]), "position startpos");
+    assert_eq!(
+        uci::position_command(&start, &[e4]),
+        "position startpos moves e2e4"
+    );
+    let mut after = start.clone();
+    after.apply(e4);
+    assert_eq!(
+        uci::position_command(&after, &[]),
+        format!("position fen {}", after.to_fen())
+    );
+
+    Ok(())
+}
+
+#[test]
+fn scripted_engine_plays_its_moves() -> Result<()> {
+    let mut engine = scripted_engine(&["e7e5", "b8c6"])?;
+    assert_eq!(engine.name(), "scripted");
+    engine.new_game()?;
+
+    let start = Position::default();
+    let go = Go {
+        move_time: Some(Duration::from_millis(10)),
+        ..Go::default()
+    };
+    let e4 = start.parse_uci("e2e4")?;
+    let e5 = engine.best_move(&start, &[e4], &go)?.unwrap();
+    assert_eq!(e5.to_string(), "e7e5");
+
+    let mut position = start.clone();
+    position.apply(e4);
+    position.apply(e5);
+    let nf3 = position.parse_uci("g1f3")?;
+    assert_eq!(
+        engine
+            .best_move(&start, &[e4, e5, nf3], &go)?
+            .map(|mv| mv.to_string()),
+        Some("b8c6".to_string())
+    );
+
+    // out of moves, the script quits
+    assert!(matches!(
+        engine.best_move(&start, &[], &go),
+        Err(EngineError::Closed)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn scripted_engine_answers_are_checked() -> Result<()> {
+    let mut engine = scripted_engine(&["e2e5", "0000"])?;
+    let go = Go::default();
+
+    assert!(matches!(
+        engine.best_move(&Position::default(), &[], &go),
+        Err(EngineError::Move(_))
+    ));
+    assert!(engine.best_move(&Position::default(), &[], &go)?.is_none());
+
+    Ok(())
+}
+
+#[test]
+fn the_uci_binary_can_be_played_against() -> Result<()> {
+    let mut engine = EngineProcess::spawn(env!("CARGO_BIN_EXE_uci"), &[])?;
+    assert!(engine.name().starts_with("bevy-chess"));
+    engine.set_option("Hash", "1")?;
+    engine.new_game()?;
+
+    // mate in one with the rook
+    let start = Position::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1")?;
+    let go = Go {
+        depth: Some(3),
+        ..Go::default()
+    };
+    assert_eq!(
+        engine.best_move(&start, &[], &go)?.map(|mv| mv.to_string()),
+        Some("a1a8".to_string())
+    );
+
+    // stopped from another thread, the engine still gives its move
+    let stopper = engine.stopper();
+    let infinite = Go {
+        infinite: true,
+        ..Go::default()
+    };
+    let stopping = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(50));
+        stopper.stop()
+    });
+    let mv = engine.best_move(&Position::default(), &[], &infinite)?;
+    stopping.join().unwrap()?;
+    assert!(mv.is_some());
+
+    Ok(())
+}
@@ -0,0 +1,190 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    process::{Command as Process, Stdio},
+    time::Duration,
+};
+
+use anyhow::Result;
+use bevy_chess::{
+    engine::{SearchLimits, MATE},
+    rules::{GameResult, Position},
+    xboard::{self, Command, Level, TimeControl, XboardError},
+};
+
+#[test]
+fn commands() -> Result<()> {
+    assert_eq!("xboard".parse(), Ok(Command::Xboard));
+    assert_eq!("protover 2".parse(), Ok(Command::Protover(2)));
+    assert_eq!(
+        "usermove e7e8q".parse(),
+        Ok(Command::UserMove("e7e8q".to_string()))
+    );
+    assert_eq!("?".parse(), Ok(Command::MoveNow));
+    assert_eq!("sd 6".parse(), Ok(Command::Sd(6)));
+    assert_eq!("st 5".parse(), Ok(Command::St(Duration::from_secs(5))));
+    assert_eq!(
+        "time 12345".parse(),
+        Ok(Command::Time(Duration::from_millis(123_450)))
+    );
+    assert_eq!(
+        "result 1-0 {White mates}".parse(),
+        Ok(Command::Result {
+            result: Some(GameResult::WhiteWins),
+            comment: "White mates".to_string(),
+        })
+    );
+    assert_eq!(
+        "setboard 8/8/8/8/8/8/8/K6k w - - 0 1".parse(),
+        Ok(Command::SetBoard(Box::new(Position::from_fen(
+            "8/8/8/8/8/8/8/K6k w - - 0 1"
+        )?)))
+    );
+    assert_eq!(
+        "random".parse::<Command>(),
+        Err(XboardError::Unknown("random".to_string()))
+    );
+    assert!(matches!(
+        "sd x".parse::<Command>(),
+        Err(XboardError::Value { .. })
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn levels_and_their_limits() {
+    assert_eq!(
+        "level 40 5 0".parse(),
+        Ok(Command::Level(Level {
+            moves_per_session: Some(40),
+            base: Duration::from_secs(300),
+            increment: Duration::ZERO,
+        }))
+    );
+    let level = match "level 0 2:30 1.5".parse() {
+        Ok(Command::Level(level)) => level,
+        other => panic!("{:?}", other),
+    };
+    assert_eq!(level.moves_per_session, None);
+    assert_eq!(level.base, Duration::from_secs(150));
+    assert_eq!(level.increment, Duration::from_millis(1500));
+
+    // the moves left until the next session come from the move number
+    let control = TimeControl {
+        level: Some(Level {
+            moves_per_session: Some(40),
+            base: Duration::from_secs(300),
+            increment: Duration::ZERO,
+        }),
+        ..TimeControl::default()
+    };
+    let clock = Some(Duration::from_secs(10));
+    assert_eq!(
+        control.limits(clock, 40),
+        SearchLimits::from_clock(Duration::from_secs(10), Duration::ZERO, Some(1))
+    );
+    assert_eq!(
+        control.limits(clock, 41),
+        SearchLimits::from_clock(Duration::from_secs(10), Duration::ZERO, Some(40))
+    );
+
+    let fixed = TimeControl {
+        move_time: Some(Duration::from_secs(2)),
+        depth: Some(5),
+        ..control
+    };
+    assert_eq!(
+        fixed.limits(clock, 1),
+        SearchLimits {
+            depth: Some(5),
+            nodes: None,
+            time: Some(Duration::from_secs(2)),
+        }
+    );
+}
+
+#[test]
+fn scores_in_centipawns_with_mates_past_100000() {
+    assert_eq!(xboard::score(-35), -35);
+    assert_eq!(xboard::score(MATE - 3), 100_002);
+    assert_eq!(xboard::score(-MATE + 2), -100_001);
+}
+
+#[test]
+fn engine_plays_a_game_over_stdin() -> Result<()> {
+    let mut engine = Process::new(env!("CARGO_BIN_EXE_uci"))
+        .arg("--xboard")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+    let mut stdin = engine.stdin.take().unwrap();
+    let mut stdout = BufReader::new(engine.stdout.take().unwrap());
+    let mut read_until = |prefix: &str| -> Result<Vec<String>> {
+        let mut lines = Vec::new();
+        loop {
+            let mut line = String::new();
+            if stdout.read_line(&mut line)? == 0 {
+                anyhow::bail!("the engine quit before {:?}", prefix);
+            }
+            let line = line.trim().to_string();
+            let done = line.starts_with(prefix);
+            lines.push(line);
+            if done {
+                return Ok(lines);
+            }
+        }
+    };
+
+    writeln!(stdin, "xboard")?;
+    writeln!(stdin, "protover 2")?;
+    let lines = read_until("feature")?;
+    assert!(lines[0].contains("usermove=1"));
+    assert!(lines[0].ends_with("done=1"));
+
+    // the engine plays black after `new`, and answers the move
+    writeln!(stdin, "new")?;
+    writeln!(stdin, "sd 2")?;
+    writeln!(stdin, "post")?;
+    writeln!(stdin, "usermove e2e4")?;
+    let lines = read_until("move")?;
+    assert!(lines.len() > 1, "no thinking output in {:?}", lines);
+    let reply = lines
+        .last()
+        .unwrap()
+        .trim_start_matches("move ")
+        .to_string();
+    let mut position = Position::default();
+    position.apply(position.parse_uci("e2e4")?);
+    position.parse_uci(&reply)?;
+
+    writeln!(stdin, "usermove e2e5")?;
+    read_until("Illegal move: e2e5")?;
+
+    // mate in one with the rook once told to play white
+    writeln!(stdin, "force")?;
+    writeln!(stdin, "setboard 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1")?;
+    writeln!(stdin, "sd 3")?;
+    writeln!(stdin, "go")?;
+    let lines = read_until("move")?;
+    assert_eq!(lines.last().unwrap(), "move a1a8");
+
+    writeln!(stdin, "ping 7")?;
+    read_until("pong 7")?;
+
+    // a ping while the engine thinks is answered once its move is out
+    writeln!(stdin, "force")?;
+    writeln!(stdin, "new")?;
+    writeln!(stdin, "sd 4")?;
+    writeln!(stdin, "go")?;
+    writeln!(stdin, "ping 1")?;
+    let lines = read_until("pong 1")?;
+    assert!(
+        lines.iter().any(|line| line.starts_with("move ")),
+        "no move before pong in {:?}",
+        lines
+    );
+    writeln!(stdin, "quit")?;
+    assert!(engine.wait()?.success());
+
+    Ok(())
+}